@@ -0,0 +1,334 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::{Combo, Key, KeyState, MouseButton, MouseState, ParseKeyError};
+
+// one input that can trigger an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Binding {
+    // the key, whatever modifiers are held
+    Key(Key),
+    // the key with exactly these modifiers
+    Combo(Combo),
+    Mouse(MouseButton),
+}
+
+impl Binding {
+    const MOUSE_NAMES: [(MouseButton, &'static str); 5] = [
+        (MouseButton::Left, "MouseLeft"),
+        (MouseButton::Right, "MouseRight"),
+        (MouseButton::Middle, "MouseMiddle"),
+        (MouseButton::X1, "MouseX1"),
+        (MouseButton::X2, "MouseX2"),
+    ];
+
+    pub fn is_pressed(&self, keys: &KeyState, mouse: &MouseState) -> bool {
+        match self {
+            Binding::Key(key) => keys.is_pressed(*key),
+            Binding::Combo(combo) => keys.is_combo_pressed(*combo),
+            Binding::Mouse(button) => mouse.is_pressed(*button),
+        }
+    }
+
+    pub fn is_just_pressed(&self, keys: &KeyState, mouse: &MouseState) -> bool {
+        match self {
+            Binding::Key(key) => keys.is_just_pressed(*key),
+            Binding::Combo(combo) => keys.is_combo_just_pressed(*combo),
+            Binding::Mouse(button) => mouse.is_just_pressed(*button),
+        }
+    }
+
+    pub fn is_just_released(&self, keys: &KeyState, mouse: &MouseState) -> bool {
+        match self {
+            Binding::Key(key) => keys.is_just_released(*key),
+            Binding::Combo(combo) => keys.is_just_released(combo.key),
+            Binding::Mouse(button) => mouse.is_just_released(*button),
+        }
+    }
+}
+
+impl From<Key> for Binding {
+    fn from(key: Key) -> Self {
+        Binding::Key(key)
+    }
+}
+
+impl From<Combo> for Binding {
+    fn from(combo: Combo) -> Self {
+        Binding::Combo(combo)
+    }
+}
+
+impl From<MouseButton> for Binding {
+    fn from(button: MouseButton) -> Self {
+        Binding::Mouse(button)
+    }
+}
+
+// written like `W`, `Ctrl+S` or `MouseLeft`
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{}", key),
+            Binding::Combo(combo) => write!(f, "{}", combo),
+            Binding::Mouse(button) => {
+                let (_, name) = Binding::MOUSE_NAMES
+                    .iter()
+                    .find(|(b, _)| b == button)
+                    .unwrap();
+                f.write_str(name)
+            }
+        }
+    }
+}
+
+impl FromStr for Binding {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some((button, _)) = Binding::MOUSE_NAMES
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(s))
+        {
+            return Ok(Binding::Mouse(*button));
+        }
+        // `+` on its own is not a key, so anything with one in it is a combo
+        if s.contains('+') {
+            return s.parse().map(Binding::Combo);
+        }
+        s.parse().map(Binding::Key)
+    }
+}
+
+#[derive(Debug)]
+pub enum ActionsError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    // the bindings of this action aren't a list of strings
+    NotAList(String),
+    Binding(ParseKeyError),
+}
+
+impl fmt::Display for ActionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionsError::Io(e) => write!(f, "couldn't read bindings: {}", e),
+            ActionsError::Toml(e) => write!(f, "bindings aren't valid toml: {}", e),
+            ActionsError::NotAList(action) => {
+                write!(f, "bindings of `{}` should be a list of strings", action)
+            }
+            ActionsError::Binding(e) => write!(f, "bad binding: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ActionsError {}
+
+impl From<std::io::Error> for ActionsError {
+    fn from(e: std::io::Error) -> Self {
+        ActionsError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for ActionsError {
+    fn from(e: toml::de::Error) -> Self {
+        ActionsError::Toml(e)
+    }
+}
+
+impl From<ParseKeyError> for ActionsError {
+    fn from(e: ParseKeyError) -> Self {
+        ActionsError::Binding(e)
+    }
+}
+
+// named actions ("move_left", "confirm"...) and the inputs bound to them.
+// saved as toml, one action per line: `move_left = ["A", "Left"]`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Actions {
+    bindings: BTreeMap<String, Vec<Binding>>,
+}
+
+impl Actions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // adds a binding to an action, creating the action if needed
+    pub fn bind(&mut self, action: &str, binding: impl Into<Binding>) {
+        let binding = binding.into();
+        let bindings = self.bindings.entry(action.to_string()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    // removes one binding from an action
+    pub fn unbind(&mut self, action: &str, binding: impl Into<Binding>) {
+        let binding = binding.into();
+        if let Some(bindings) = self.bindings.get_mut(action) {
+            bindings.retain(|b| *b != binding);
+        }
+    }
+
+    // replaces every binding of an action
+    pub fn rebind(&mut self, action: &str, bindings: Vec<Binding>) {
+        self.bindings.insert(action.to_string(), bindings);
+    }
+
+    pub fn remove(&mut self, action: &str) {
+        self.bindings.remove(action);
+    }
+
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.bindings.get(action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.bindings.keys().map(String::as_str)
+    }
+
+    // actions bound to this input, handy for spotting conflicts when rebinding
+    pub fn actions_bound_to(&self, binding: impl Into<Binding>) -> Vec<&str> {
+        let binding = binding.into();
+        self.bindings
+            .iter()
+            .filter(|(_, bindings)| bindings.contains(&binding))
+            .map(|(action, _)| action.as_str())
+            .collect()
+    }
+
+    pub fn is_pressed(&self, action: &str, keys: &KeyState, mouse: &MouseState) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|b| b.is_pressed(keys, mouse))
+    }
+
+    pub fn is_just_pressed(&self, action: &str, keys: &KeyState, mouse: &MouseState) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|b| b.is_just_pressed(keys, mouse))
+    }
+
+    // released this frame and not held through another binding
+    pub fn is_just_released(&self, action: &str, keys: &KeyState, mouse: &MouseState) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|b| b.is_just_released(keys, mouse))
+            && !self.is_pressed(action, keys, mouse)
+    }
+
+    // replaces the bindings of every action in the toml, others are kept.
+    // this way a config file only needs the actions the player changed
+    pub fn merge_toml(&mut self, text: &str) -> Result<(), ActionsError> {
+        let table: toml::Table = text.parse()?;
+        for (action, value) in table {
+            let list = value
+                .as_array()
+                .ok_or_else(|| ActionsError::NotAList(action.clone()))?;
+            let mut bindings = Vec::new();
+            for binding in list {
+                let binding = binding
+                    .as_str()
+                    .ok_or_else(|| ActionsError::NotAList(action.clone()))?;
+                bindings.push(binding.parse()?);
+            }
+            self.bindings.insert(action, bindings);
+        }
+        Ok(())
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, ActionsError> {
+        let mut actions = Actions::new();
+        actions.merge_toml(text)?;
+        Ok(actions)
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut table = toml::Table::new();
+        for (action, bindings) in &self.bindings {
+            let list = bindings
+                .iter()
+                .map(|b| toml::Value::String(b.to_string()))
+                .collect();
+            table.insert(action.clone(), toml::Value::Array(list));
+        }
+        table.to_string()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ActionsError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    // loads the player's bindings over these defaults
+    pub fn load_over(&mut self, path: impl AsRef<Path>) -> Result<(), ActionsError> {
+        self.merge_toml(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ActionsError> {
+        std::fs::write(path, self.to_toml())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Modifiers;
+
+    #[test]
+    fn toml_round_trip() {
+        let mut actions = Actions::new();
+        actions.bind("left", Key::A);
+        actions.bind("left", Key::Left);
+        actions.bind("save", Modifiers::CTRL + Key::S);
+        actions.bind("redo", (Modifiers::CTRL | Modifiers::SHIFT) + Key::Z);
+        actions.bind("fire", MouseButton::Left);
+        actions.bind("jump", Key::Key1);
+        actions.rebind("unbound", Vec::new());
+
+        let loaded = Actions::from_toml(&actions.to_toml()).unwrap();
+        assert_eq!(loaded, actions);
+        assert_eq!(
+            loaded.bindings("left"),
+            &[Binding::Key(Key::A), Binding::Key(Key::Left)]
+        );
+    }
+
+    #[test]
+    fn merge_only_replaces_listed_actions() {
+        let mut actions = Actions::new();
+        actions.bind("left", Key::A);
+        actions.bind("right", Key::D);
+
+        actions.merge_toml(r#"left = ["Q", "MouseRight"]"#).unwrap();
+        assert_eq!(
+            actions.bindings("left"),
+            &[Binding::Key(Key::Q), Binding::Mouse(MouseButton::Right)]
+        );
+        assert_eq!(actions.bindings("right"), &[Binding::Key(Key::D)]);
+    }
+
+    #[test]
+    fn bad_toml_is_an_error() {
+        assert!(matches!(
+            Actions::from_toml("left = \"A\""),
+            Err(ActionsError::NotAList(action)) if action == "left"
+        ));
+        assert!(matches!(
+            Actions::from_toml("left = [1]"),
+            Err(ActionsError::NotAList(_))
+        ));
+        assert!(matches!(
+            Actions::from_toml(r#"left = ["NotAKey"]"#),
+            Err(ActionsError::Binding(_))
+        ));
+        assert!(matches!(
+            Actions::from_toml("left = ["),
+            Err(ActionsError::Toml(_))
+        ));
+    }
+}
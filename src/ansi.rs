@@ -0,0 +1,217 @@
+// the xterm 256 color palette and conversions between it and rgb. named colors and the
+// first 16 values are up to the terminal's theme, see `Palette` to pick them
+
+use crate::Color;
+
+// how many colors a terminal can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorDepth {
+    // any rgb color
+    TrueColor,
+    // the xterm 256 color palette
+    Ansi256,
+    // just the 16 named colors (the linux console, old terminals...)
+    Ansi16,
+}
+
+// xterm's default rgb values of the 16 named colors, in ansi order
+pub(crate) const NAMED: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::DarkRed, (205, 0, 0)),
+    (Color::DarkGreen, (0, 205, 0)),
+    (Color::DarkYellow, (205, 205, 0)),
+    (Color::DarkBlue, (0, 0, 238)),
+    (Color::DarkMagenta, (205, 0, 205)),
+    (Color::DarkCyan, (0, 205, 205)),
+    (Color::Grey, (229, 229, 229)),
+    (Color::DarkGrey, (127, 127, 127)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (92, 92, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+// levels of each channel in the 6x6x6 color cube (indices 16 to 231)
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+// 4x4 bayer matrix for ordered dithering
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+// rgb value of an entry of the xterm 256 color palette: the 16 named colors with xterm's
+// defaults, a 6x6x6 color cube and a ramp of 24 grays
+pub fn ansi_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => NAMED[index as usize].1,
+        16..=231 => {
+            let i = index - 16;
+            (
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        232..=255 => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+// position of a named color in the 16 color palette
+pub(crate) fn named_index(color: Color) -> Option<usize> {
+    NAMED.iter().position(|(c, _)| *c == color)
+}
+
+// rgb value of any color, `None` for `Color::Reset` which is up to the terminal
+pub fn to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Reset => None,
+        Color::Rgb { r, g, b } => Some((r, g, b)),
+        Color::AnsiValue(index) => Some(ansi_to_rgb(index)),
+        named => NAMED.iter().find(|(c, _)| *c == named).map(|(_, rgb)| *rgb),
+    }
+}
+
+// weighted so that it is closer to how different colors look than plain rgb distance
+pub(crate) fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let mean_r = (r1 as i32 + r2 as i32) / 2;
+    let dr = r1 as i32 - r2 as i32;
+    let dg = g1 as i32 - g2 as i32;
+    let db = b1 as i32 - b2 as i32;
+    ((((512 + mean_r) * dr * dr) >> 8) + 4 * dg * dg + (((767 - mean_r) * db * db) >> 8)) as u32
+}
+
+fn nearest_level(value: u8) -> usize {
+    (0..CUBE_LEVELS.len())
+        .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - value as i32).abs())
+        .unwrap_or(0)
+}
+
+// nearest entry of the 256 color palette. the first 16 are skipped since terminals
+// let users change them, the cube and the grays are the same everywhere
+pub fn rgb_to_ansi256(rgb: (u8, u8, u8)) -> u8 {
+    let (r, g, b) = rgb;
+    let cube = 16 + 36 * nearest_level(r) + 6 * nearest_level(g) + nearest_level(b);
+
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23) as usize;
+
+    if distance(rgb, ansi_to_rgb(gray as u8)) < distance(rgb, ansi_to_rgb(cube as u8)) {
+        gray as u8
+    } else {
+        cube as u8
+    }
+}
+
+// nearest of the 16 named colors
+pub fn rgb_to_ansi16(rgb: (u8, u8, u8)) -> Color {
+    NAMED
+        .iter()
+        .min_by_key(|(_, named)| distance(rgb, *named))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+// nudges an rgb color by the cell's spot in the bayer matrix, so gradients become patterns
+// of the two nearest colors instead of bands. `spread` is about the gap between palette colors
+fn dither(rgb: (u8, u8, u8), x: u32, y: u32, spread: i32) -> (u8, u8, u8) {
+    let threshold = BAYER[(y % 4) as usize][(x % 4) as usize] as i32;
+    let offset = (threshold * 2 - 15) * spread / 32;
+    let nudge = |channel: u8| (channel as i32 + offset).clamp(0, 255) as u8;
+    (nudge(rgb.0), nudge(rgb.1), nudge(rgb.2))
+}
+
+// the color to send to a terminal with `depth` colors. named colors are kept as they are,
+// so they still follow the terminal's theme. `dither_at` is the cell position to dither with
+pub(crate) fn downsample(color: Color, depth: ColorDepth, dither_at: Option<(u32, u32)>) -> Color {
+    match (depth, color) {
+        (ColorDepth::TrueColor, _) => color,
+        (ColorDepth::Ansi256, Color::Rgb { r, g, b }) => {
+            let rgb = match dither_at {
+                Some((x, y)) => dither((r, g, b), x, y, 40),
+                None => (r, g, b),
+            };
+            Color::AnsiValue(rgb_to_ansi256(rgb))
+        }
+        (ColorDepth::Ansi16, Color::AnsiValue(index)) if index < 16 => NAMED[index as usize].0,
+        (ColorDepth::Ansi16, Color::Rgb { .. } | Color::AnsiValue(_)) => {
+            let Some(rgb) = to_rgb(color) else {
+                return color;
+            };
+            let rgb = match dither_at {
+                Some((x, y)) => dither(rgb, x, y, 128),
+                None => rgb,
+            };
+            rgb_to_ansi16(rgb)
+        }
+        _ => color,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ansi_to_rgb_covers_the_cube_and_grays() {
+        assert_eq!(ansi_to_rgb(1), (205, 0, 0));
+        assert_eq!(ansi_to_rgb(16), (0, 0, 0));
+        assert_eq!(ansi_to_rgb(67), (95, 135, 175));
+        assert_eq!(ansi_to_rgb(231), (255, 255, 255));
+        assert_eq!(ansi_to_rgb(232), (8, 8, 8));
+        assert_eq!(ansi_to_rgb(255), (238, 238, 238));
+    }
+
+    #[test]
+    fn rgb_to_ansi256_finds_every_cube_and_gray_entry() {
+        for index in 16..=255 {
+            assert_eq!(rgb_to_ansi256(ansi_to_rgb(index)), index);
+        }
+    }
+
+    #[test]
+    fn rgb_to_ansi256_picks_the_nearest() {
+        assert_eq!(rgb_to_ansi256((90, 140, 170)), 67);
+        // grays go to the gray ramp, which is finer than the cube's grays
+        assert_eq!(rgb_to_ansi256((128, 128, 128)), 244);
+        assert_eq!(rgb_to_ansi256((0, 0, 0)), 16);
+        assert_eq!(rgb_to_ansi256((255, 255, 255)), 231);
+        // the named colors are never used, users can change them
+        assert!((0..=255).all(|v| rgb_to_ansi256((v, v / 2, 255 - v)) >= 16));
+    }
+
+    #[test]
+    fn rgb_to_ansi16_picks_the_nearest_named() {
+        assert_eq!(rgb_to_ansi16((250, 10, 10)), Color::Red);
+        assert_eq!(rgb_to_ansi16((120, 120, 130)), Color::DarkGrey);
+        assert_eq!(rgb_to_ansi16((0, 0, 0)), Color::Black);
+    }
+
+    #[test]
+    fn downsample_keeps_named_colors() {
+        assert_eq!(downsample(Color::Red, ColorDepth::Ansi16, None), Color::Red);
+        assert_eq!(
+            downsample(Color::Reset, ColorDepth::Ansi256, None),
+            Color::Reset
+        );
+        assert_eq!(
+            downsample(
+                Color::Rgb {
+                    r: 95,
+                    g: 135,
+                    b: 175
+                },
+                ColorDepth::Ansi256,
+                None
+            ),
+            Color::AnsiValue(67)
+        );
+        assert_eq!(
+            downsample(Color::AnsiValue(9), ColorDepth::Ansi16, None),
+            Color::Red
+        );
+    }
+}
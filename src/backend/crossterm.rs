@@ -1,68 +1,558 @@
 use crossterm::{
     cursor,
-    event::{KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags},
-    execute,
+    event::{
+        EnableFocusChange, EnableMouseCapture, Event as CEvent, KeyboardEnhancementFlags,
+        MouseButton as CMouseButton, MouseEvent, MouseEventKind, PushKeyboardEnhancementFlags,
+    },
+    execute, queue,
     style::{Print, SetAttribute, SetBackgroundColor, SetForegroundColor},
-    terminal,
+    terminal::{self, BeginSynchronizedUpdate, EndSynchronizedUpdate},
 };
 
+#[cfg(not(unix))]
+use crossterm::event;
 #[cfg(not(feature = "crossterm_events"))]
 use device_query::{DeviceQuery, DeviceState};
+#[cfg(not(feature = "crossterm_events"))]
+use std::collections::HashSet;
+#[cfg(not(feature = "crossterm_events"))]
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+    mpsc::{self, Receiver},
+};
 
 #[cfg(feature = "crossterm_events")]
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use super::releases::ReleaseSynthesizer;
+#[cfg(feature = "crossterm_events")]
+use crossterm::event::ModifierKeyCode;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
 
-use std::collections::HashSet;
-use std::io::{Stdout, Write, stdout};
+use std::fs::File;
+use std::io::{IsTerminal, Stdout, Write, stdout};
 use std::time::{Duration, Instant};
 
 use crate::cell::Cell;
-use crate::{Key, backend::Backend};
+use crate::{
+    ColorDepth, Event, Key, Modifiers, MouseButton, Palette, WoblError, ansi, backend::Backend,
+};
 
-pub struct CrosstermBackend {
-    pressed_keys: HashSet<Key>,
-    just_pressed: HashSet<Key>,
-    released_keys: HashSet<Key>,
+#[cfg(unix)]
+use super::{
+    parse::{Parsed, Reply},
+    reader::TerminalReader,
+};
+use super::{
+    restore::{self, Output, Screen},
+    signals::SignalFlag,
+    terminfo,
+};
+
+// draws to stdout by default, see `tty` and `with_writer` for other outputs
+pub struct CrosstermBackend<W: Write = Stdout> {
     #[cfg(not(feature = "crossterm_events"))]
-    device_state: DeviceState,
+    key_receiver: Option<Receiver<(Key, bool)>>,
+    #[cfg(not(feature = "crossterm_events"))]
+    key_poller_running: Arc<AtomicBool>,
+    #[cfg(not(feature = "crossterm_events"))]
+    held_modifiers: HashSet<Key>,
+    quit_signals: SignalFlag,
+    #[cfg(unix)]
+    suspend_signals: SignalFlag,
+    #[cfg(unix)]
+    resume_signals: SignalFlag,
+    #[cfg(unix)]
+    resize_signals: SignalFlag,
+    // where keys, the mouse and answers to queries are read from, when reading input
+    #[cfg(unix)]
+    reader: Option<TerminalReader>,
     frame_start: Instant,
     milis: u64,
-    stdout: Stdout,
+    writer: W,
+    output: Output,
+    // raw mode and reading keys, mouse and terminal events. off when only drawing to a writer
+    reads_input: bool,
+    // what `enter` did, to undo on drop. only for writers, the terminal's is kept in `restore`
+    // where the panic hook and the signals can get at it
+    taken: bool,
+    screen: Screen,
+    palette_changed: bool,
 
     front_buffer: Vec<Cell>,
     back_buffer: Vec<Cell>,
+    // draws every cell on the next flush, not just the changed ones
+    full_redraw: bool,
+
+    color_depth: ColorDepth,
+    dithering: bool,
+    // whether the terminal shows frames all at once instead of half drawn, asked when entering
+    terminal_synchronizes: bool,
+    // overrides the detection
+    synchronized_output: Option<bool>,
+
+    // whether the terminal has the kitty protocol and sends key releases, asked when entering
+    #[cfg(feature = "crossterm_events")]
+    terminal_sends_releases: bool,
+    // overrides the detection
+    #[cfg(feature = "crossterm_events")]
+    synthesize_releases: Option<bool>,
+    #[cfg(feature = "crossterm_events")]
+    releases: ReleaseSynthesizer,
+    // every key as an escape code, modifiers on their own too, see `set_report_all_keys`
+    report_all_keys: bool,
+
+    // the game's palette, sent to the terminal if `push_palette` is on
+    palette: Option<Palette>,
+    push_palette: bool,
+
+    // inline games are drawn on lines of the normal screen starting at `origin_row`
+    inline: bool,
+    origin_row: u16,
+    keep_last_frame: bool,
 
     width: u32,
     height: u32,
 }
 
-impl CrosstermBackend {
-    pub fn new() -> Self {
-        let mut stdout = stdout();
-        execute!(
-            stdout,
-            terminal::EnterAlternateScreen,
-            crossterm::cursor::Hide,
-            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES),
-            //PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES),
-        )
-        .unwrap();
-        terminal::enable_raw_mode().unwrap();
-
-        CrosstermBackend {
-            stdout,
-            pressed_keys: HashSet::new(),
-            just_pressed: HashSet::new(),
-            released_keys: HashSet::new(),
+impl CrosstermBackend<Stdout> {
+    // fullscreen, on the alternate screen. fails if stdout isn't a terminal, or (without
+    // `crossterm_events`) if the keyboard can't be read
+    pub fn new() -> Result<Self, WoblError> {
+        let stdout = stdout();
+        if !stdout.is_terminal() {
+            return Err(WoblError::NotATerminal);
+        }
+        Self::open(stdout, Output::Stdout, true)
+    }
+
+    // same as `new` with `set_inline(true)`
+    pub fn inline() -> Result<Self, WoblError> {
+        let mut backend = Self::new()?;
+        backend.set_inline(true);
+        Ok(backend)
+    }
+
+    // gives the terminal back right away, for games that set their own panic hook
+    // after creating the backend (which replaces the one that does this).
+    // works for `tty()` backends too, it is only here so it can be called without a type
+    pub fn restore_terminal() {
+        restore::restore_terminal();
+    }
+}
+
+impl CrosstermBackend<File> {
+    // draws straight to the terminal the game runs in, so stdout can be piped or redirected
+    // (to log to it, or to print a result for a script)
+    pub fn tty() -> Result<Self, WoblError> {
+        let tty = restore::open_tty().map_err(|_| WoblError::NotATerminal)?;
+        CrosstermBackend::open(tty, Output::Tty, true)
+    }
+}
+
+impl<W: Write> CrosstermBackend<W> {
+    // only draws, into any writer: a socket, a pty, or a `Vec<u8>` to compare against a golden file.
+    // nothing is read (no keys, mouse or resizes), raw mode is left alone and signals aren't caught,
+    // a panic can't restore the writer either, so `Drop` is what gives it back
+    pub fn with_writer(writer: W) -> Result<Self, WoblError> {
+        Self::open(writer, Output::Other, false)
+    }
+
+    fn open(writer: W, output: Output, reads_input: bool) -> Result<Self, WoblError> {
+        #[cfg(not(feature = "crossterm_events"))]
+        let key_poller_running = Arc::new(AtomicBool::new(true));
+        #[cfg(not(feature = "crossterm_events"))]
+        let key_receiver = if reads_input {
+            Some(Self::spawn_key_poller(key_poller_running.clone())?)
+        } else {
+            None
+        };
+
+        #[cfg(unix)]
+        let reader = if reads_input {
+            Some(TerminalReader::open().map_err(|_| WoblError::NotATerminal)?)
+        } else {
+            None
+        };
+
+        // a writer isn't the terminal ctrl-c and ctrl-z come from
+        let signals = |flag: fn() -> SignalFlag| {
+            if reads_input {
+                flag()
+            } else {
+                SignalFlag::none()
+            }
+        };
+
+        Ok(CrosstermBackend {
+            writer,
+            output,
+            reads_input,
+            taken: false,
+            screen: Screen::Fullscreen,
+            palette_changed: false,
+            #[cfg(not(feature = "crossterm_events"))]
+            key_receiver,
+            #[cfg(not(feature = "crossterm_events"))]
+            key_poller_running,
             #[cfg(not(feature = "crossterm_events"))]
-            device_state: DeviceState::new(),
+            held_modifiers: HashSet::new(),
+            quit_signals: signals(SignalFlag::quit),
+            #[cfg(unix)]
+            suspend_signals: signals(SignalFlag::suspend),
+            #[cfg(unix)]
+            resume_signals: signals(SignalFlag::resume),
+            #[cfg(unix)]
+            resize_signals: signals(SignalFlag::resize),
+            #[cfg(unix)]
+            reader,
             frame_start: Instant::now(),
             milis: 0,
             front_buffer: Vec::new(),
             back_buffer: Vec::new(),
+            full_redraw: false,
+            color_depth: terminfo::detect_color_depth(),
+            dithering: false,
+            // the guess for writers and terminals that aren't asked
+            terminal_synchronizes: terminfo::detect_synchronized_output(),
+            synchronized_output: None,
+            #[cfg(feature = "crossterm_events")]
+            terminal_sends_releases: false,
+            #[cfg(feature = "crossterm_events")]
+            synthesize_releases: None,
+            #[cfg(feature = "crossterm_events")]
+            releases: ReleaseSynthesizer::new(),
+            report_all_keys: false,
+            inline: false,
+            origin_row: 0,
+            keep_last_frame: true,
+            palette: None,
+            push_palette: false,
             width: 0,
             height: 0,
+        })
+    }
+
+    // what the terminal was detected to support, colors it can't show are mapped to the nearest one
+    pub fn color_depth(&self) -> ColorDepth {
+        self.color_depth
+    }
+
+    // overrides the detected color depth, for when the guess is wrong
+    pub fn set_color_depth(&mut self, depth: ColorDepth) {
+        self.color_depth = depth;
+        self.full_redraw = true;
+    }
+
+    // dithers rgb colors when they are mapped to fewer colors, nicer for gradients
+    // but noisy for flat colors. off by default
+    pub fn set_dithering(&mut self, dithering: bool) {
+        self.dithering = dithering;
+        self.full_redraw = true;
+    }
+
+    // whether frames are sent as synchronized updates, detected from the terminal
+    pub fn synchronized_output(&self) -> bool {
+        self.synchronized_output
+            .unwrap_or(self.terminal_synchronizes)
+    }
+
+    // overrides the detection. terminals without support ignore it, so turning it on
+    // for one that wasn't recognized is safe
+    pub fn set_synchronized_output(&mut self, synchronized: bool) {
+        self.synchronized_output = Some(synchronized);
+    }
+
+    // `Some(true)` always guesses key releases from the key repeat, `Some(false)` never does
+    // (for terminals that send releases but aren't detected), `None` decides from the terminal
+    #[cfg(feature = "crossterm_events")]
+    pub fn set_synthesized_releases(&mut self, synthesize: Option<bool>) {
+        self.synthesize_releases = synthesize;
+    }
+
+    // how long a key can go without repeating before it counts as released: `delay` before
+    // the first repeat and `interval` after it. they have to be a bit longer than the os
+    // key repeat settings, the defaults (800ms and 100ms) fit most setups
+    #[cfg(feature = "crossterm_events")]
+    pub fn set_release_timing(&mut self, delay: Duration, interval: Duration) {
+        self.releases.delay = delay;
+        self.releases.interval = interval;
+    }
+
+    // kitty protocol terminals only: also reports keys that type nothing on their own, like shift
+    // or ctrl as run and crouch buttons. off by default since the terminal then sends the key instead
+    // of the text, so `typed_text` misses what altgr, dead keys, compose and input methods (ime)
+    // make and only gets the character of the key itself (shifted for letters).
+    // only does something before the engine is created
+    #[cfg(feature = "crossterm_events")]
+    pub fn set_report_all_keys(&mut self, report: bool) {
+        self.report_all_keys = report;
+    }
+
+    // sets the terminal's 16 colors and default colors to the game's palette (`Wobl::set_palette`)
+    // while the game runs, so it looks like it does with sdl. uses OSC 4, 10 and 11, which most
+    // terminals support and the rest ignore. the terminal's colors are put back on exit
+    pub fn set_push_palette(&mut self, push: bool) {
+        self.push_palette = push;
+        if !self.is_taken() {
+            return;
+        }
+        let _ = if push {
+            self.push_palette()
+        } else {
+            self.reset_palette()
+        };
+    }
+
+    // draws below the cursor like normal command line output instead of taking the whole screen,
+    // reserving as many lines as the engine's height. scrollback is left alone.
+    // only does something before the engine is created. the backend asks the terminal where the
+    // cursor is, so creating the engine fails with `NotATerminal` for `with_writer` backends
+    pub fn set_inline(&mut self, inline: bool) {
+        self.inline = inline;
+    }
+
+    // inline only: leaves the last frame on screen when the backend is dropped (the default),
+    // or clears the lines it used so the game leaves no trace
+    pub fn set_keep_last_frame(&mut self, keep: bool) {
+        self.keep_last_frame = keep;
+    }
+
+    // also makes sure the terminal is restored on a panic or SIGQUIT, before the report is printed.
+    // if this fails half way, dropping the backend undoes what was done
+    fn enter(&mut self) -> Result<(), WoblError> {
+        let screen = if self.inline {
+            Screen::Inline { last_row: None }
+        } else {
+            Screen::Fullscreen
+        };
+        if self.reads_input {
+            restore::mark_taken(screen, self.output);
+        } else {
+            self.taken = true;
+            self.screen = screen;
+        }
+        if !self.inline {
+            execute!(self.writer, terminal::EnterAlternateScreen)?;
+        }
+        execute!(self.writer, crossterm::cursor::Hide)?;
+        if self.push_palette {
+            self.push_palette()?;
+        }
+        if self.reads_input {
+            let mut flags = KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+                | KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES;
+            // alternate keys bring the base layout key along for the scancode. only on unix, where
+            // keys aren't parsed by crossterm, which would swap in the shifted key and drop shift
+            #[cfg(unix)]
+            {
+                flags |= KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS;
+            }
+            if self.report_all_keys {
+                flags |= KeyboardEnhancementFlags::REPORT_ALL_KEYS_AS_ESCAPE_CODES;
+            }
+            execute!(
+                self.writer,
+                EnableMouseCapture,
+                // device_query reads keys from every window, focus tells which ones are ours
+                EnableFocusChange,
+                PushKeyboardEnhancementFlags(flags),
+            )?;
+            terminal::enable_raw_mode()?;
+            self.ask_terminal()?;
+        }
+        Ok(())
+    }
+
+    // asks the terminal if it knows the kitty protocol (so it sends key releases) and synchronized
+    // updates (DECRQM for mode 2026). terminals skip questions they don't know, so no answer is a no
+    #[cfg(unix)]
+    fn ask_terminal(&mut self) -> Result<(), WoblError> {
+        let Some(reader) = &mut self.reader else {
+            return Ok(());
+        };
+        // no answer at all, the guesses stay
+        let Some(replies) = reader.query(&mut self.writer, "\x1b[?u\x1b[?2026$p")? else {
+            return Ok(());
+        };
+        #[cfg(feature = "crossterm_events")]
+        {
+            self.terminal_sends_releases = replies.contains(&Reply::KeyboardFlags);
+        }
+        // set, reset or always set. 0 is a mode it doesn't know and 4 one it can't turn on
+        self.terminal_synchronizes = replies.iter().any(|reply| {
+            matches!(
+                reply,
+                Reply::Mode {
+                    mode: 2026,
+                    setting: 1..=3
+                }
+            )
+        });
+        Ok(())
+    }
+
+    // crossterm can only ask about the kitty protocol here, synchronized updates stay guessed
+    #[cfg(not(unix))]
+    fn ask_terminal(&mut self) -> Result<(), WoblError> {
+        #[cfg(feature = "crossterm_events")]
+        {
+            self.terminal_sends_releases =
+                terminal::supports_keyboard_enhancement().unwrap_or(false);
+        }
+        Ok(())
+    }
+
+    fn push_palette(&mut self) -> Result<(), WoblError> {
+        let Some(palette) = self.palette else {
+            return Ok(());
+        };
+        let rgb = |(r, g, b): (u8, u8, u8)| format!("rgb:{:02x}/{:02x}/{:02x}", r, g, b);
+        let mut sequence = String::new();
+        for (index, color) in palette.colors.into_iter().enumerate() {
+            sequence.push_str(&format!("\x1b]4;{};{}\x07", index, rgb(color)));
+        }
+        sequence.push_str(&format!("\x1b]10;{}\x07", rgb(palette.foreground)));
+        sequence.push_str(&format!("\x1b]11;{}\x07", rgb(palette.background)));
+        if self.reads_input {
+            restore::set_palette_changed();
+        } else {
+            self.palette_changed = true;
+        }
+        execute!(self.writer, Print(sequence))?;
+        Ok(())
+    }
+
+    // only if it was changed
+    fn reset_palette(&mut self) -> Result<(), WoblError> {
+        if self.take_palette_changed() {
+            execute!(self.writer, Print("\x1b]104\x07\x1b]110\x07\x1b]111\x07"))?;
+        }
+        Ok(())
+    }
+
+    // undoes `enter` through the writer, unless a panic or signal already did
+    fn give_back(&mut self) {
+        let taken = if self.reads_input {
+            restore::release()
+        } else {
+            std::mem::take(&mut self.taken)
+        };
+        if taken {
+            let screen = self.screen();
+            let palette_changed = self.take_palette_changed();
+            restore::restore_with(&mut self.writer, self.reads_input, screen, palette_changed);
+        }
+    }
+
+    // whether the terminal (or writer) is the game's right now
+    fn is_taken(&self) -> bool {
+        if self.reads_input {
+            restore::is_taken()
+        } else {
+            self.taken
+        }
+    }
+
+    fn screen(&self) -> Screen {
+        if self.reads_input {
+            restore::screen()
+        } else {
+            self.screen
+        }
+    }
+
+    fn set_screen(&mut self, screen: Screen) {
+        if self.reads_input {
+            restore::set_screen(screen);
+        } else {
+            self.screen = screen;
+        }
+    }
+
+    fn take_palette_changed(&mut self) -> bool {
+        if self.reads_input {
+            restore::take_palette_changed()
+        } else {
+            std::mem::take(&mut self.palette_changed)
+        }
+    }
+
+    // gives the terminal back and stops the process like ctrl-z normally would.
+    // raw mode turns ctrl-z into a key, so it is caught here too
+    #[cfg(unix)]
+    fn suspend(&mut self) -> Result<(), WoblError> {
+        self.give_back();
+        let _ = signal_hook::low_level::emulate_default_handler(signal_hook::consts::SIGTSTP);
+        // the shell continued the game (`fg`)
+        self.resume()
+    }
+
+    // takes the terminal again after being stopped. the screen might have anything on it by now,
+    // so everything is drawn again on the next flush
+    #[cfg(unix)]
+    fn resume(&mut self) -> Result<(), WoblError> {
+        // stopped by SIGSTOP, which can't be caught, so the terminal wasn't given back
+        self.give_back();
+        // the SIGCONT of a resume from `suspend`, already handled
+        self.resume_signals.take();
+        self.enter()?;
+        if self.inline {
+            self.reserve_lines(self.height)?;
+        }
+        self.full_redraw = true;
+        Ok(())
+    }
+
+    // makes room for `height` lines below the cursor, scrolling the terminal if needed
+    fn reserve_lines(&mut self, height: u32) -> Result<(), WoblError> {
+        let (column, _) = self.cursor_position()?;
+        // start on a fresh line if something (like a prompt) is on the current one
+        if column > 0 {
+            execute!(self.writer, Print("\r\n"))?;
+        }
+        for _ in 1..height {
+            execute!(self.writer, Print("\r\n"))?;
+        }
+        let (_, last_row) = self.cursor_position()?;
+        self.origin_row = last_row.saturating_sub(height.saturating_sub(1) as u16);
+        execute!(
+            self.writer,
+            cursor::MoveTo(0, self.origin_row),
+            terminal::Clear(terminal::ClearType::FromCursorDown)
+        )?;
+        self.set_screen(Screen::Inline {
+            last_row: Some(last_row),
+        });
+        Ok(())
+    }
+
+    // asked from the terminal the backend draws to. a writer can't answer, so inline mode
+    // needs `new` or `tty`
+    #[cfg(unix)]
+    fn cursor_position(&mut self) -> Result<(u16, u16), WoblError> {
+        let Some(reader) = &mut self.reader else {
+            return Err(WoblError::NotATerminal);
+        };
+        // DA1 answered but the position didn't come, so it isn't a terminal that knows where it is
+        let replies = reader
+            .query(&mut self.writer, "\x1b[6n")?
+            .unwrap_or_default();
+        replies
+            .into_iter()
+            .find_map(|reply| match reply {
+                Reply::CursorPosition { column, row } => Some((column, row)),
+                _ => None,
+            })
+            .ok_or(WoblError::NotATerminal)
+    }
+
+    // the console's cursor, which is where stdout and the tty both draw
+    #[cfg(not(unix))]
+    fn cursor_position(&mut self) -> Result<(u16, u16), WoblError> {
+        if !self.reads_input {
+            return Err(WoblError::NotATerminal);
         }
+        Ok(cursor::position()?)
     }
 
     #[cfg(not(feature = "crossterm_events"))]
@@ -128,6 +618,14 @@ impl CrosstermBackend {
             Keycode::F10 => Key::F10,
             Keycode::F11 => Key::F11,
             Keycode::F12 => Key::F12,
+            Keycode::F13 => Key::F13,
+            Keycode::F14 => Key::F14,
+            Keycode::F15 => Key::F15,
+            Keycode::F16 => Key::F16,
+            Keycode::F17 => Key::F17,
+            Keycode::F18 => Key::F18,
+            Keycode::F19 => Key::F19,
+            Keycode::F20 => Key::F20,
 
             // Whitespace and control
             Keycode::Space => Key::Space,
@@ -136,75 +634,183 @@ impl CrosstermBackend {
             Keycode::Escape => Key::Escape,
             Keycode::Backspace => Key::Backspace,
 
-            // Modifiers and symbols (if needed)
-            Keycode::LShift | Keycode::RShift => Key::Shift,
-            Keycode::LControl | Keycode::RControl => Key::Ctrl,
-            Keycode::LAlt | Keycode::RAlt => Key::Alt,
+            // Navigation and editing
+            Keycode::Insert => Key::Insert,
+            Keycode::Delete => Key::Delete,
+            Keycode::Home => Key::Home,
+            Keycode::End => Key::End,
+            Keycode::PageUp => Key::PageUp,
+            Keycode::PageDown => Key::PageDown,
+
+            // Modifiers
+            Keycode::LShift => Key::LeftShift,
+            Keycode::RShift => Key::RightShift,
+            Keycode::LControl => Key::LeftCtrl,
+            Keycode::RControl => Key::RightCtrl,
+            Keycode::LAlt | Keycode::LOption => Key::LeftAlt,
+            Keycode::RAlt | Keycode::ROption => Key::RightAlt,
+            Keycode::LMeta | Keycode::Command => Key::LeftSuper,
+            Keycode::RMeta | Keycode::RCommand => Key::RightSuper,
             Keycode::CapsLock => Key::CapsLock,
 
-            // Unknown / unhandled
-            _ => Key::Unknown,
+            // Numpad
+            Keycode::Numpad0 => Key::Numpad0,
+            Keycode::Numpad1 => Key::Numpad1,
+            Keycode::Numpad2 => Key::Numpad2,
+            Keycode::Numpad3 => Key::Numpad3,
+            Keycode::Numpad4 => Key::Numpad4,
+            Keycode::Numpad5 => Key::Numpad5,
+            Keycode::Numpad6 => Key::Numpad6,
+            Keycode::Numpad7 => Key::Numpad7,
+            Keycode::Numpad8 => Key::Numpad8,
+            Keycode::Numpad9 => Key::Numpad9,
+            Keycode::NumpadAdd => Key::NumpadAdd,
+            Keycode::NumpadSubtract => Key::NumpadSubtract,
+            Keycode::NumpadMultiply => Key::NumpadMultiply,
+            Keycode::NumpadDivide => Key::NumpadDivide,
+            Keycode::NumpadDecimal => Key::NumpadDecimal,
+            Keycode::NumpadEnter => Key::NumpadEnter,
+            Keycode::NumpadEquals => Key::NumpadEquals,
+
+            // Symbols
+            Keycode::Minus => Key::Minus,
+            Keycode::Equal => Key::Equals,
+            Keycode::LeftBracket => Key::LeftBracket,
+            Keycode::RightBracket => Key::RightBracket,
+            Keycode::BackSlash => Key::Backslash,
+            Keycode::Semicolon => Key::Semicolon,
+            Keycode::Apostrophe => Key::Apostrophe,
+            Keycode::Grave => Key::Grave,
+            Keycode::Comma => Key::Comma,
+            Keycode::Dot => Key::Period,
+            Keycode::Slash => Key::Slash,
         }
     }
+
+    // device_query only gives the current state, so a thread polls it much faster than
+    // the frame rate and sends every change. this way taps between two frames aren't lost
     #[cfg(not(feature = "crossterm_events"))]
-    fn update_keys(&mut self) {
-        let old_keys = self.pressed_keys.clone();
+    fn spawn_key_poller(running: Arc<AtomicBool>) -> Result<Receiver<(Key, bool)>, WoblError> {
+        let (sender, receiver) = mpsc::channel();
+        let (ready_sender, ready_receiver) = mpsc::sync_channel(1);
+        std::thread::spawn(move || {
+            // the device state can't be sent between threads, so the thread makes it
+            // and reports back whether that worked
+            let Some(device_state) = DeviceState::checked_new() else {
+                let _ = ready_sender.send(false);
+                return;
+            };
+            let _ = ready_sender.send(true);
+            let mut previous = Vec::new();
+            while running.load(Ordering::Relaxed) {
+                let keys = device_state.get_keys();
+                let released = previous.iter().filter(|k| !keys.contains(k));
+                let pressed = keys.iter().filter(|k| !previous.contains(k));
+                let changes = released
+                    .map(|k| (Self::map_key(k), false))
+                    .chain(pressed.map(|k| (Self::map_key(k), true)));
+                for change in changes {
+                    if sender.send(change).is_err() {
+                        return;
+                    }
+                }
+                previous = keys;
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        });
+        match ready_receiver.recv() {
+            Ok(true) => Ok(receiver),
+            _ => Err(WoblError::NoKeyboard),
+        }
+    }
 
-        let current_keys: HashSet<Key> = self
-            .device_state
-            .get_keys()
-            .iter()
-            .map(Self::map_key)
-            .filter(|&x| x != Key::Unknown)
-            .collect();
+    #[cfg(not(feature = "crossterm_events"))]
+    fn poll_device_keys(&mut self, events: &mut Vec<Event>) {
+        let Some(key_receiver) = &self.key_receiver else {
+            return;
+        };
+        while let Ok((key, down)) = key_receiver.try_recv() {
+            if Modifiers::from_key(key).is_some() {
+                if down {
+                    self.held_modifiers.insert(key);
+                } else {
+                    self.held_modifiers.remove(&key);
+                }
+            }
+            let modifiers = self
+                .held_modifiers
+                .iter()
+                .filter_map(|&k| Modifiers::from_key(k))
+                .fold(Modifiers::NONE, |all, modifier| all | modifier);
 
-        self.just_pressed = current_keys.difference(&old_keys).cloned().collect();
-        self.released_keys = old_keys.difference(&current_keys).cloned().collect();
-        self.pressed_keys = current_keys;
+            // device_query already reads keys by position on linux and macos (and by symbol on
+            // windows), there is nothing else to tell the scancode from
+            events.push(if down {
+                Event::KeyDown {
+                    key,
+                    scancode: key,
+                    modifiers,
+                }
+            } else {
+                Event::KeyUp {
+                    key,
+                    scancode: key,
+                    modifiers,
+                }
+            });
+        }
     }
 
     #[cfg(feature = "crossterm_events")]
-    fn map_key(code: KeyCode) -> Key {
-        match code {
-            // Letters
-            KeyCode::Char('a') => Key::A,
-            KeyCode::Char('b') => Key::B,
-            KeyCode::Char('c') => Key::C,
-            KeyCode::Char('d') => Key::D,
-            KeyCode::Char('e') => Key::E,
-            KeyCode::Char('f') => Key::F,
-            KeyCode::Char('g') => Key::G,
-            KeyCode::Char('h') => Key::H,
-            KeyCode::Char('i') => Key::I,
-            KeyCode::Char('j') => Key::J,
-            KeyCode::Char('k') => Key::K,
-            KeyCode::Char('l') => Key::L,
-            KeyCode::Char('m') => Key::M,
-            KeyCode::Char('n') => Key::N,
-            KeyCode::Char('o') => Key::O,
-            KeyCode::Char('p') => Key::P,
-            KeyCode::Char('q') => Key::Q,
-            KeyCode::Char('r') => Key::R,
-            KeyCode::Char('s') => Key::S,
-            KeyCode::Char('t') => Key::T,
-            KeyCode::Char('u') => Key::U,
-            KeyCode::Char('v') => Key::V,
-            KeyCode::Char('w') => Key::W,
-            KeyCode::Char('x') => Key::X,
-            KeyCode::Char('y') => Key::Y,
-            KeyCode::Char('z') => Key::Z,
-
-            // Numbers
-            KeyCode::Char('0') => Key::Key0,
-            KeyCode::Char('1') => Key::Key1,
-            KeyCode::Char('2') => Key::Key2,
-            KeyCode::Char('3') => Key::Key3,
-            KeyCode::Char('4') => Key::Key4,
-            KeyCode::Char('5') => Key::Key5,
-            KeyCode::Char('6') => Key::Key6,
-            KeyCode::Char('7') => Key::Key7,
-            KeyCode::Char('8') => Key::Key8,
-            KeyCode::Char('9') => Key::Key9,
+    fn map_key(key_event: &KeyEvent) -> Key {
+        // with the kitty protocol the numpad is told apart from the rest of the keyboard
+        if key_event.state.contains(KeyEventState::KEYPAD) {
+            let key = Self::map_keypad(key_event.code);
+            if key != Key::Unknown {
+                return key;
+            }
+        }
+
+        match key_event.code {
+            // Letters, shifted or not
+            KeyCode::Char('a' | 'A') => Key::A,
+            KeyCode::Char('b' | 'B') => Key::B,
+            KeyCode::Char('c' | 'C') => Key::C,
+            KeyCode::Char('d' | 'D') => Key::D,
+            KeyCode::Char('e' | 'E') => Key::E,
+            KeyCode::Char('f' | 'F') => Key::F,
+            KeyCode::Char('g' | 'G') => Key::G,
+            KeyCode::Char('h' | 'H') => Key::H,
+            KeyCode::Char('i' | 'I') => Key::I,
+            KeyCode::Char('j' | 'J') => Key::J,
+            KeyCode::Char('k' | 'K') => Key::K,
+            KeyCode::Char('l' | 'L') => Key::L,
+            KeyCode::Char('m' | 'M') => Key::M,
+            KeyCode::Char('n' | 'N') => Key::N,
+            KeyCode::Char('o' | 'O') => Key::O,
+            KeyCode::Char('p' | 'P') => Key::P,
+            KeyCode::Char('q' | 'Q') => Key::Q,
+            KeyCode::Char('r' | 'R') => Key::R,
+            KeyCode::Char('s' | 'S') => Key::S,
+            KeyCode::Char('t' | 'T') => Key::T,
+            KeyCode::Char('u' | 'U') => Key::U,
+            KeyCode::Char('v' | 'V') => Key::V,
+            KeyCode::Char('w' | 'W') => Key::W,
+            KeyCode::Char('x' | 'X') => Key::X,
+            KeyCode::Char('y' | 'Y') => Key::Y,
+            KeyCode::Char('z' | 'Z') => Key::Z,
+
+            // Numbers, shifted ones as on a US layout
+            KeyCode::Char('0' | ')') => Key::Key0,
+            KeyCode::Char('1' | '!') => Key::Key1,
+            KeyCode::Char('2' | '@') => Key::Key2,
+            KeyCode::Char('3' | '#') => Key::Key3,
+            KeyCode::Char('4' | '$') => Key::Key4,
+            KeyCode::Char('5' | '%') => Key::Key5,
+            KeyCode::Char('6' | '^') => Key::Key6,
+            KeyCode::Char('7' | '&') => Key::Key7,
+            KeyCode::Char('8' | '*') => Key::Key8,
+            KeyCode::Char('9' | '(') => Key::Key9,
 
             // Function keys
             KeyCode::F(1) => Key::F1,
@@ -219,6 +825,18 @@ impl CrosstermBackend {
             KeyCode::F(10) => Key::F10,
             KeyCode::F(11) => Key::F11,
             KeyCode::F(12) => Key::F12,
+            KeyCode::F(13) => Key::F13,
+            KeyCode::F(14) => Key::F14,
+            KeyCode::F(15) => Key::F15,
+            KeyCode::F(16) => Key::F16,
+            KeyCode::F(17) => Key::F17,
+            KeyCode::F(18) => Key::F18,
+            KeyCode::F(19) => Key::F19,
+            KeyCode::F(20) => Key::F20,
+            KeyCode::F(21) => Key::F21,
+            KeyCode::F(22) => Key::F22,
+            KeyCode::F(23) => Key::F23,
+            KeyCode::F(24) => Key::F24,
 
             // Arrows
             KeyCode::Up => Key::Up,
@@ -229,22 +847,48 @@ impl CrosstermBackend {
             // Control
             KeyCode::Esc => Key::Escape,
             KeyCode::Enter => Key::Enter,
-            KeyCode::Tab => Key::Tab,
+            KeyCode::Tab | KeyCode::BackTab => Key::Tab,
             KeyCode::Backspace => Key::Backspace,
             KeyCode::Char(' ') => Key::Space,
 
-            // Symbols
-            KeyCode::Char('-') => Key::Minus,
-            KeyCode::Char('=') => Key::Equals,
-            KeyCode::Char('[') => Key::LeftBracket,
-            KeyCode::Char(']') => Key::RightBracket,
-            KeyCode::Char('\\') => Key::Backslash,
-            KeyCode::Char(';') => Key::Semicolon,
-            KeyCode::Char('\'') => Key::Apostrophe,
-            KeyCode::Char('`') => Key::Grave,
-            KeyCode::Char(',') => Key::Comma,
-            KeyCode::Char('.') => Key::Period,
-            KeyCode::Char('/') => Key::Slash,
+            // Navigation and editing
+            KeyCode::Insert => Key::Insert,
+            KeyCode::Delete => Key::Delete,
+            KeyCode::Home => Key::Home,
+            KeyCode::End => Key::End,
+            KeyCode::PageUp => Key::PageUp,
+            KeyCode::PageDown => Key::PageDown,
+            KeyCode::PrintScreen => Key::PrintScreen,
+            KeyCode::ScrollLock => Key::ScrollLock,
+            KeyCode::Pause => Key::Pause,
+            KeyCode::NumLock => Key::NumLock,
+            KeyCode::Menu => Key::Menu,
+            KeyCode::CapsLock => Key::CapsLock,
+
+            // Modifiers, only reported by terminals with the kitty protocol
+            KeyCode::Modifier(ModifierKeyCode::LeftShift) => Key::LeftShift,
+            KeyCode::Modifier(ModifierKeyCode::RightShift) => Key::RightShift,
+            KeyCode::Modifier(ModifierKeyCode::LeftControl) => Key::LeftCtrl,
+            KeyCode::Modifier(ModifierKeyCode::RightControl) => Key::RightCtrl,
+            KeyCode::Modifier(ModifierKeyCode::LeftAlt) => Key::LeftAlt,
+            KeyCode::Modifier(ModifierKeyCode::RightAlt) => Key::RightAlt,
+            KeyCode::Modifier(ModifierKeyCode::LeftSuper) => Key::LeftSuper,
+            KeyCode::Modifier(ModifierKeyCode::RightSuper) => Key::RightSuper,
+            KeyCode::Modifier(ModifierKeyCode::LeftMeta) => Key::LeftSuper,
+            KeyCode::Modifier(ModifierKeyCode::RightMeta) => Key::RightSuper,
+
+            // Symbols, shifted ones as on a US layout
+            KeyCode::Char('-' | '_') => Key::Minus,
+            KeyCode::Char('=' | '+') => Key::Equals,
+            KeyCode::Char('[' | '{') => Key::LeftBracket,
+            KeyCode::Char(']' | '}') => Key::RightBracket,
+            KeyCode::Char('\\' | '|') => Key::Backslash,
+            KeyCode::Char(';' | ':') => Key::Semicolon,
+            KeyCode::Char('\'' | '"') => Key::Apostrophe,
+            KeyCode::Char('`' | '~') => Key::Grave,
+            KeyCode::Char(',' | '<') => Key::Comma,
+            KeyCode::Char('.' | '>') => Key::Period,
+            KeyCode::Char('/' | '?') => Key::Slash,
 
             // Unknown / unmapped
             _ => Key::Unknown,
@@ -252,64 +896,262 @@ impl CrosstermBackend {
     }
 
     #[cfg(feature = "crossterm_events")]
-    fn update_keys(&mut self) {
-        self.just_pressed.clear();
-        self.released_keys.clear();
-        while event::poll(Duration::from_millis(0)).unwrap() {
-            if let Event::Key(key_event) = event::read().unwrap() {
-                let key = Self::map_key(key_event.code);
-                if key == Key::Unknown {
-                    continue;
+    fn map_keypad(code: KeyCode) -> Key {
+        match code {
+            KeyCode::Char('0') => Key::Numpad0,
+            KeyCode::Char('1') => Key::Numpad1,
+            KeyCode::Char('2') => Key::Numpad2,
+            KeyCode::Char('3') => Key::Numpad3,
+            KeyCode::Char('4') => Key::Numpad4,
+            KeyCode::Char('5') => Key::Numpad5,
+            KeyCode::Char('6') => Key::Numpad6,
+            KeyCode::Char('7') => Key::Numpad7,
+            KeyCode::Char('8') => Key::Numpad8,
+            KeyCode::Char('9') => Key::Numpad9,
+            KeyCode::Char('+') => Key::NumpadAdd,
+            KeyCode::Char('-') => Key::NumpadSubtract,
+            KeyCode::Char('*') => Key::NumpadMultiply,
+            KeyCode::Char('/') => Key::NumpadDivide,
+            KeyCode::Char('.' | ',') => Key::NumpadDecimal,
+            KeyCode::Enter => Key::NumpadEnter,
+            KeyCode::Char('=') => Key::NumpadEquals,
+            _ => Key::Unknown,
+        }
+    }
+
+    #[cfg(feature = "crossterm_events")]
+    fn map_modifiers(modifiers: KeyModifiers) -> Modifiers {
+        let mut mods = Modifiers::NONE;
+        mods.set(Modifiers::SHIFT, modifiers.contains(KeyModifiers::SHIFT));
+        mods.set(Modifiers::CTRL, modifiers.contains(KeyModifiers::CONTROL));
+        mods.set(Modifiers::ALT, modifiers.contains(KeyModifiers::ALT));
+        mods.set(
+            Modifiers::SUPER,
+            modifiers.intersects(KeyModifiers::SUPER | KeyModifiers::META),
+        );
+        mods
+    }
+
+    // `base` is the key in the same spot on a us layout, see `parse::kitty_key`
+    #[cfg(feature = "crossterm_events")]
+    fn map_key_event(&mut self, key_event: &KeyEvent, base: Option<KeyCode>) -> Event {
+        let key = Self::map_key(key_event);
+        let modifiers = Self::map_modifiers(key_event.modifiers);
+
+        // kitty protocol terminals send the base layout key when it isn't the key itself. the others
+        // (and windows, where crossterm reads keys) only say what a key typed, so the scancode is
+        // the key there, the same as when the base layout key is left out
+        let scancode = match base {
+            Some(code) => Self::map_key(&KeyEvent::new_with_kind_and_state(
+                code,
+                key_event.modifiers,
+                key_event.kind,
+                key_event.state,
+            )),
+            None => key,
+        };
+
+        // without `report_all_keys` enter, tab and backspace still come as plain bytes, and those
+        // are never released
+        let legacy = !self.report_all_keys && matches!(key, Key::Enter | Key::Tab | Key::Backspace);
+        if self.synthesizes_key_releases() || legacy {
+            match key_event.kind {
+                KeyEventKind::Press | KeyEventKind::Repeat => {
+                    return self.releases.press(key, scancode, modifiers);
                 }
+                KeyEventKind::Release => self.releases.forget(key),
+            }
+        }
+        match key_event.kind {
+            KeyEventKind::Press => Event::KeyDown {
+                key,
+                scancode,
+                modifiers,
+            },
+            KeyEventKind::Release => Event::KeyUp {
+                key,
+                scancode,
+                modifiers,
+            },
+            KeyEventKind::Repeat => Event::KeyRepeat {
+                key,
+                scancode,
+                modifiers,
+            },
+        }
+    }
+
+    // typed characters, ctrl and alt combinations are shortcuts and not text
+    fn map_text_event(key_event: &KeyEvent) -> Option<Event> {
+        if key_event.kind == KeyEventKind::Release
+            || key_event
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SUPER)
+        {
+            return None;
+        }
+        match key_event.code {
+            // keys reported as escape codes are unshifted, typed text already is what it is
+            KeyCode::Char(c)
+                if key_event.modifiers.contains(KeyModifiers::SHIFT)
+                    != key_event.state.contains(KeyEventState::CAPS_LOCK) =>
+            {
+                Some(Event::Text(c.to_uppercase().to_string()))
+            }
+            KeyCode::Char(c) => Some(Event::Text(c.to_string())),
+            _ => None,
+        }
+    }
+
+    fn is_ctrl_c(key_event: &KeyEvent) -> bool {
+        key_event.kind == KeyEventKind::Press
+            && key_event.modifiers.contains(KeyModifiers::CONTROL)
+            && matches!(key_event.code, KeyCode::Char('c' | 'C'))
+    }
+
+    #[cfg(unix)]
+    fn is_ctrl_z(key_event: &KeyEvent) -> bool {
+        key_event.kind == KeyEventKind::Press
+            && key_event.modifiers.contains(KeyModifiers::CONTROL)
+            && matches!(key_event.code, KeyCode::Char('z' | 'Z'))
+    }
+
+    fn map_mouse_button(button: CMouseButton) -> MouseButton {
+        match button {
+            CMouseButton::Left => MouseButton::Left,
+            CMouseButton::Right => MouseButton::Right,
+            CMouseButton::Middle => MouseButton::Middle,
+        }
+    }
+
+    // `origin_row` is where the game starts on the screen, so positions stay in game cells
+    fn map_mouse_event(mouse_event: MouseEvent, origin_row: u16) -> Event {
+        let x = mouse_event.column as i32;
+        let y = mouse_event.row as i32 - origin_row as i32;
+        match mouse_event.kind {
+            MouseEventKind::Down(button) => Event::MouseDown {
+                button: Self::map_mouse_button(button),
+                x,
+                y,
+            },
+            MouseEventKind::Up(button) => Event::MouseUp {
+                button: Self::map_mouse_button(button),
+                x,
+                y,
+            },
+            MouseEventKind::ScrollUp => Event::MouseWheel { x: 0, y: 1 },
+            MouseEventKind::ScrollDown => Event::MouseWheel { x: 0, y: -1 },
+            MouseEventKind::ScrollLeft => Event::MouseWheel { x: -1, y: 0 },
+            MouseEventKind::ScrollRight => Event::MouseWheel { x: 1, y: 0 },
+            MouseEventKind::Drag(_) | MouseEventKind::Moved => Event::MouseMove { x, y },
+        }
+    }
 
-                match key_event.kind {
-                    KeyEventKind::Press => {
-                        if !self.pressed_keys.contains(&key) {
-                            self.just_pressed.insert(key);
-                        }
-                        self.pressed_keys.insert(key);
+    // reads all pending terminal events. key events only come from here with `crossterm_events`,
+    // otherwise they come from device_query and only text and the mouse are read from here
+    fn poll_terminal_events(&mut self, events: &mut Vec<Event>) -> Result<(), WoblError> {
+        while let Some((event, base)) = self.read_terminal_event()? {
+            // device_query already knows where keys are
+            #[cfg(not(feature = "crossterm_events"))]
+            let _ = base;
+            match event {
+                CEvent::Key(key_event) => {
+                    #[cfg(feature = "crossterm_events")]
+                    events.push(self.map_key_event(&key_event, base));
+                    events.extend(Self::map_text_event(&key_event));
+                    // raw mode turns ctrl-c into a key instead of SIGINT
+                    if Self::is_ctrl_c(&key_event) {
+                        events.push(Event::Quit);
                     }
-                    KeyEventKind::Release => {
-                        self.pressed_keys.remove(&key);
-                        self.released_keys.insert(key);
+                    // the rest of the events are read after the game is back
+                    #[cfg(unix)]
+                    if Self::is_ctrl_z(&key_event) {
+                        return self.suspend();
                     }
-                    KeyEventKind::Repeat => {}
                 }
+                CEvent::Mouse(mouse_event) => {
+                    events.push(Self::map_mouse_event(mouse_event, self.origin_row))
+                }
+                CEvent::Paste(text) => events.push(Event::Text(text)),
+                CEvent::Resize(width, height) => events.push(Event::Resize {
+                    width: width as u32,
+                    height: height as u32,
+                }),
+                CEvent::FocusGained => events.push(Event::FocusGained),
+                CEvent::FocusLost => events.push(Event::FocusLost),
             }
         }
+        Ok(())
+    }
+
+    // the next event the terminal sent, `None` once they are all read. keys come with the key
+    // in the same spot on a us layout when the terminal says which one that is
+    #[cfg(unix)]
+    fn read_terminal_event(&mut self) -> Result<Option<(CEvent, Option<KeyCode>)>, WoblError> {
+        if self.resize_signals.take() {
+            let (width, height) = terminal::size()?;
+            return Ok(Some((CEvent::Resize(width, height), None)));
+        }
+        let Some(reader) = &mut self.reader else {
+            return Ok(None);
+        };
+        loop {
+            match reader.read()? {
+                Some(Parsed::Key(key_event, base)) => {
+                    return Ok(Some((CEvent::Key(key_event), base)));
+                }
+                Some(Parsed::Event(event)) => return Ok(Some((event, None))),
+                // a late answer to a query that timed out
+                Some(Parsed::Reply(_)) => {}
+                None => return Ok(None),
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn read_terminal_event(&mut self) -> Result<Option<(CEvent, Option<KeyCode>)>, WoblError> {
+        if event::poll(Duration::ZERO)? {
+            Ok(Some((event::read()?, None)))
+        } else {
+            Ok(None)
+        }
     }
 
-    fn draw_cell_internal(&mut self, x: u32, y: u32, cell: &Cell) {
-        let fg = cell.fg;
-        let bg = cell.bg;
+    fn draw_cell_internal(&mut self, x: u32, y: u32, cell: &Cell) -> Result<(), WoblError> {
+        let dither_at = self.dithering.then_some((x, y));
+        let fg = ansi::downsample(cell.fg, self.color_depth, dither_at);
+        let bg = ansi::downsample(cell.bg, self.color_depth, dither_at);
         let atr = cell.atr.clone();
         for &atribute in atr.iter() {
-            execute!(self.stdout, SetAttribute(atribute)).unwrap();
+            queue!(self.writer, SetAttribute(atribute))?;
         }
-        execute!(
-            self.stdout,
-            cursor::MoveTo(x as u16, y as u16),
+        queue!(
+            self.writer,
+            cursor::MoveTo(x as u16, y as u16 + self.origin_row),
             SetForegroundColor(fg),
             SetBackgroundColor(bg),
             Print(cell.ch)
-        )
-        .unwrap();
+        )?;
+        Ok(())
     }
 }
 
-impl Backend for CrosstermBackend {
-    fn init(&mut self, _name: &str, width: u32, height: u32) {
+impl<W: Write> Backend for CrosstermBackend<W> {
+    fn init(&mut self, _name: &str, width: u32, height: u32) -> Result<(), WoblError> {
         self.back_buffer = vec![Cell::empty(); (width * height) as usize];
         self.front_buffer = self.back_buffer.clone();
-        self.width = width as u32;
-        self.height = height as u32;
+        self.width = width;
+        self.height = height;
+        self.enter()?;
+        if self.inline {
+            self.reserve_lines(height)?;
+        }
+        Ok(())
     }
 
     fn wait_frame(&mut self) {
         //self.flush();
 
-        self.update_keys();
-
         let frame_duration = Duration::from_millis(self.milis);
 
         let elapsed = self.frame_start.elapsed();
@@ -319,6 +1161,13 @@ impl Backend for CrosstermBackend {
         self.frame_start = Instant::now();
     }
 
+    fn set_palette(&mut self, palette: &Palette) {
+        self.palette = Some(*palette);
+        if self.push_palette && self.is_taken() {
+            let _ = self.push_palette();
+        }
+    }
+
     fn set_fps(&mut self, fps: Option<u32>) {
         if fps.is_none() {
             return;
@@ -326,23 +1175,44 @@ impl Backend for CrosstermBackend {
         self.milis = (1000.0 / (fps.unwrap() as f32)) as u64;
     }
 
-    fn is_key_pressed(&self, key: Key) -> bool {
-        self.pressed_keys.contains(&key)
-    }
-
-    fn is_key_just_pressed(&self, key: Key) -> bool {
-        self.just_pressed.contains(&key)
+    fn poll_events(&mut self, events: &mut Vec<Event>) -> Result<(), WoblError> {
+        if self.quit_signals.take() {
+            events.push(Event::Quit);
+        }
+        #[cfg(unix)]
+        if self.suspend_signals.take() {
+            self.suspend()?;
+        } else if self.resume_signals.take() {
+            self.resume()?;
+        }
+        if !self.reads_input {
+            return Ok(());
+        }
+        #[cfg(not(feature = "crossterm_events"))]
+        self.poll_device_keys(events);
+        self.poll_terminal_events(events)?;
+        #[cfg(feature = "crossterm_events")]
+        self.releases.release_expired(events);
+        Ok(())
     }
 
-    fn is_key_just_released(&self, key: Key) -> bool {
-        self.released_keys.contains(&key)
+    #[cfg(feature = "crossterm_events")]
+    fn synthesizes_key_releases(&self) -> bool {
+        self.synthesize_releases
+            .unwrap_or(!self.terminal_sends_releases)
     }
 
-    fn draw_cell(&mut self, x: u32, y: u32, cell: &Cell) {
+    fn draw_cell(&mut self, x: u32, y: u32, cell: &Cell) -> Result<(), WoblError> {
         self.front_buffer[(x + self.width * y) as usize] = cell.clone();
+        Ok(())
     }
 
-    fn flush(&mut self) {
+    // everything is queued and written at once at the end of the frame
+    fn flush(&mut self) -> Result<(), WoblError> {
+        let synchronized = self.synchronized_output();
+        if synchronized {
+            queue!(self.writer, BeginSynchronizedUpdate)?;
+        }
         for y in 0..self.height {
             for x in 0..self.width {
                 let idx = (x + self.width * y) as usize;
@@ -350,26 +1220,72 @@ impl Backend for CrosstermBackend {
                 let back = self.back_buffer[idx].clone();
                 let front = self.front_buffer[idx].clone();
 
-                if front != back {
-                    self.draw_cell_internal(x, y, &front);
+                if front != back || self.full_redraw {
+                    self.draw_cell_internal(x, y, &front)?;
                 }
             }
         }
+        self.full_redraw = false;
         self.back_buffer = self.front_buffer.clone();
         self.front_buffer = vec![Cell::empty(); self.front_buffer.len()];
-        self.stdout.flush().unwrap();
+        if synchronized {
+            queue!(self.writer, EndSynchronizedUpdate)?;
+        }
+        self.writer.flush()?;
+        Ok(())
     }
 }
 
-impl Drop for CrosstermBackend {
+impl<W: Write> Drop for CrosstermBackend<W> {
     fn drop(&mut self) {
-        terminal::disable_raw_mode().unwrap();
-        execute!(
-            self.stdout,
-            terminal::LeaveAlternateScreen,
-            crossterm::cursor::Show,
-            PopKeyboardEnhancementFlags
-        )
-        .unwrap();
+        #[cfg(not(feature = "crossterm_events"))]
+        self.key_poller_running.store(false, Ordering::Relaxed);
+        // after a panic the terminal is already restored and the report is below the game
+        if self.inline && !self.keep_last_frame && self.is_taken() {
+            let _ = execute!(
+                self.writer,
+                cursor::MoveTo(0, self.origin_row),
+                terminal::Clear(terminal::ClearType::FromCursorDown)
+            );
+            self.set_screen(Screen::Inline { last_row: None });
+        }
+        self.give_back();
+    }
+}
+
+#[cfg(all(test, unix, feature = "crossterm_events"))]
+mod tests {
+    use super::super::parse::{self, Parse};
+    use super::*;
+
+    fn key_event(backend: &mut CrosstermBackend<Vec<u8>>, bytes: &[u8]) -> Event {
+        let Parse::Done(Parsed::Key(key_event, base), _) = parse::parse(bytes) else {
+            panic!("{:?} isn't a key", bytes);
+        };
+        backend.map_key_event(&key_event, base)
+    }
+
+    #[test]
+    fn base_layout_key_is_the_scancode() {
+        let mut backend = CrosstermBackend::with_writer(Vec::new()).unwrap();
+        backend.set_synthesized_releases(Some(false));
+        // z on azerty, where w is on a us layout
+        assert_eq!(
+            key_event(&mut backend, b"\x1b[122::119;5u"),
+            Event::KeyDown {
+                key: Key::Z,
+                scancode: Key::W,
+                modifiers: Modifiers::CTRL,
+            }
+        );
+        // the same spot on both, so the terminal leaves the base layout key out
+        assert_eq!(
+            key_event(&mut backend, b"\x1b[115;5:3u"),
+            Event::KeyUp {
+                key: Key::S,
+                scancode: Key::S,
+                modifiers: Modifiers::CTRL,
+            }
+        );
     }
 }
@@ -1,18 +1,35 @@
-use crate::{Key, cell};
+use crate::{Event, Palette, WoblError, cell};
 
 mod crossterm;
+#[cfg(unix)]
+mod parse;
+#[cfg(unix)]
+mod reader;
+#[cfg(feature = "crossterm_events")]
+mod releases;
+mod restore;
 mod sdl;
+mod signals;
+mod style;
+mod terminfo;
 
 pub use crossterm::CrosstermBackend;
 pub use sdl::SDLBackend;
 
 pub trait Backend {
-    fn init(&mut self, name: &str, width: u32, height: u32);
-    fn is_key_pressed(&self, key: Key) -> bool;
-    fn is_key_just_pressed(&self, key: Key) -> bool;
-    fn is_key_just_released(&self, key: Key) -> bool;
-    fn draw_cell(&mut self, x: u32, y: u32, cell: &cell::Cell);
+    fn init(&mut self, name: &str, width: u32, height: u32) -> Result<(), WoblError>;
+    // pushes every input event since the last call, in order
+    fn poll_events(&mut self, events: &mut Vec<Event>) -> Result<(), WoblError>;
+    fn draw_cell(&mut self, x: u32, y: u32, cell: &cell::Cell) -> Result<(), WoblError>;
     fn wait_frame(&mut self);
-    fn flush(&mut self);
+    fn flush(&mut self) -> Result<(), WoblError>;
     fn set_fps(&mut self, fps: Option<u32>);
+    // true if the input can't tell when keys are released and guesses it instead,
+    // so holding keys is less reliable
+    fn synthesizes_key_releases(&self) -> bool {
+        false
+    }
+    // the rgb values to draw named and reset colors with. terminals have their own,
+    // so backends that draw to one can ignore it
+    fn set_palette(&mut self, _palette: &Palette) {}
 }
@@ -0,0 +1,559 @@
+use crossterm::event::{
+    Event as CEvent, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers, MediaKeyCode,
+    ModifierKeyCode, MouseButton as CMouseButton, MouseEvent, MouseEventKind,
+};
+
+// what the terminal answered to a question, see `TerminalReader::query`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Reply {
+    // `CSI ? flags u`, only terminals with the kitty keyboard protocol know the question
+    KeyboardFlags,
+    // `CSI ? mode ; setting $ y` (DECRPM). setting 0 is an unknown mode, 1 set, 2 reset,
+    // 3 always set and 4 always reset
+    Mode { mode: u16, setting: u8 },
+    // `CSI ? ... c` (DA1), every terminal answers this one
+    DeviceAttributes,
+    // `CSI row ; column R`, from 0
+    CursorPosition { column: u16, row: u16 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Parsed {
+    // and the key in the same spot on a us layout, if the terminal said (see `kitty_key`)
+    Key(KeyEvent, Option<KeyCode>),
+    // the mouse and focus
+    Event(CEvent),
+    Reply(Reply),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Parse {
+    // the start of a sequence, the rest hasn't been read yet
+    Incomplete,
+    // bytes that mean nothing to us, to throw away
+    Skip(usize),
+    // and how many bytes it took
+    Done(Parsed, usize),
+}
+
+const ESC: u8 = 0x1b;
+
+// a sequence that never ends is thrown away after this many bytes
+const MAX_SEQUENCE: usize = 64;
+
+// reads the first key, mouse event or reply at the start of `buffer`. the buffer should have all
+// the terminal sent so far: an escape with nothing after it is the escape key
+pub(crate) fn parse(buffer: &[u8]) -> Parse {
+    match buffer {
+        [] => Parse::Incomplete,
+        [ESC] => key(KeyCode::Esc, KeyModifiers::NONE, 1),
+        [ESC, b'[', ..] => csi(buffer),
+        [ESC, b'O'] => Parse::Incomplete,
+        [ESC, b'O', last, ..] => match ss3_key(*last) {
+            Some(code) => key(code, KeyModifiers::NONE, 3),
+            None => Parse::Skip(3),
+        },
+        [ESC, ESC, ..] => key(KeyCode::Esc, KeyModifiers::NONE, 1),
+        // alt sends an escape before what the key types
+        [ESC, rest @ ..] => match parse(rest) {
+            Parse::Done(Parsed::Key(mut event, base), len) => {
+                event.modifiers |= KeyModifiers::ALT;
+                Parse::Done(Parsed::Key(event, base), len + 1)
+            }
+            Parse::Incomplete => Parse::Incomplete,
+            _ => Parse::Skip(1),
+        },
+        [b'\r' | b'\n', ..] => key(KeyCode::Enter, KeyModifiers::NONE, 1),
+        [b'\t', ..] => key(KeyCode::Tab, KeyModifiers::NONE, 1),
+        [0x7f | 0x08, ..] => key(KeyCode::Backspace, KeyModifiers::NONE, 1),
+        [0, ..] => key(KeyCode::Char(' '), KeyModifiers::CONTROL, 1),
+        // ctrl and a letter, then ctrl and 4 to 7
+        [c @ 0x01..=0x1a, ..] => key(
+            KeyCode::Char((c - 1 + b'a') as char),
+            KeyModifiers::CONTROL,
+            1,
+        ),
+        [c @ 0x1c..=0x1f, ..] => key(
+            KeyCode::Char((c - 0x1c + b'4') as char),
+            KeyModifiers::CONTROL,
+            1,
+        ),
+        [first, ..] => utf8(buffer, *first),
+    }
+}
+
+fn key(code: KeyCode, modifiers: KeyModifiers, len: usize) -> Parse {
+    Parse::Done(Parsed::Key(KeyEvent::new(code, modifiers), None), len)
+}
+
+fn utf8(buffer: &[u8], first: u8) -> Parse {
+    let len = match first {
+        0x00..=0x7f => 1,
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => return Parse::Skip(1),
+    };
+    if buffer.len() < len {
+        return Parse::Incomplete;
+    }
+    let Some(c) = std::str::from_utf8(&buffer[..len])
+        .ok()
+        .and_then(|s| s.chars().next())
+    else {
+        return Parse::Skip(1);
+    };
+    // typed text, an uppercase letter was typed with shift
+    let modifiers = if c.is_uppercase() {
+        KeyModifiers::SHIFT
+    } else {
+        KeyModifiers::NONE
+    };
+    key(KeyCode::Char(c), modifiers, len)
+}
+
+fn ss3_key(last: u8) -> Option<KeyCode> {
+    Some(match last {
+        b'A' => KeyCode::Up,
+        b'B' => KeyCode::Down,
+        b'C' => KeyCode::Right,
+        b'D' => KeyCode::Left,
+        b'H' => KeyCode::Home,
+        b'F' => KeyCode::End,
+        b'P' => KeyCode::F(1),
+        b'Q' => KeyCode::F(2),
+        b'R' => KeyCode::F(3),
+        b'S' => KeyCode::F(4),
+        _ => return None,
+    })
+}
+
+// `ESC [`, then parameters, then a final byte from `@` to `~`
+fn csi(buffer: &[u8]) -> Parse {
+    // the old mouse encoding is 3 raw bytes after `ESC [ M`
+    if buffer.get(2) == Some(&b'M') {
+        return match buffer.get(3..6) {
+            Some(&[cb, cx, cy]) => done(old_mouse(cb, cx, cy), 6),
+            _ => Parse::Incomplete,
+        };
+    }
+    let Some(end) = buffer
+        .iter()
+        .skip(2)
+        .position(|b| (0x40..=0x7e).contains(b))
+        .map(|i| i + 2)
+    else {
+        return if buffer.len() > MAX_SEQUENCE {
+            Parse::Skip(buffer.len())
+        } else {
+            Parse::Incomplete
+        };
+    };
+    let len = end + 1;
+    let Ok(params) = std::str::from_utf8(&buffer[2..end]) else {
+        return Parse::Skip(len);
+    };
+    let parsed = match (params.as_bytes().first(), buffer[end]) {
+        (Some(b'<'), b'M' | b'm') => sgr_mouse(&params[1..], buffer[end] == b'm'),
+        (Some(b'?'), b'u') => Some(Parsed::Reply(Reply::KeyboardFlags)),
+        (Some(b'?'), b'c') => Some(Parsed::Reply(Reply::DeviceAttributes)),
+        (Some(b'?'), b'y') => mode_report(&params[1..]),
+        (None, b'I') => Some(Parsed::Event(CEvent::FocusGained)),
+        (None, b'O') => Some(Parsed::Event(CEvent::FocusLost)),
+        (_, b'R') => cursor_position(params),
+        (_, b'~') => special_key(params),
+        (_, b'u') => kitty_key(params),
+        (_, b'M') => rxvt_mouse(params),
+        (_, b'Z') => key_event(KeyCode::BackTab, KeyModifiers::SHIFT, KeyEventKind::Press),
+        (_, last) => modified_key(params, last),
+    };
+    done(parsed, len)
+}
+
+fn done(parsed: Option<Parsed>, len: usize) -> Parse {
+    match parsed {
+        Some(parsed) => Parse::Done(parsed, len),
+        None => Parse::Skip(len),
+    }
+}
+
+fn key_event(code: KeyCode, modifiers: KeyModifiers, kind: KeyEventKind) -> Option<Parsed> {
+    Some(Parsed::Key(
+        KeyEvent::new_with_kind(code, modifiers, kind),
+        None,
+    ))
+}
+
+// `1 ; modifiers : kind`, modifiers are 1 + a bit per modifier
+fn modifiers_and_kind(field: Option<&str>) -> (KeyModifiers, KeyEventState, KeyEventKind) {
+    let mut parts = field.unwrap_or("").split(':');
+    let mask = parts
+        .next()
+        .and_then(|m| m.parse::<u16>().ok())
+        .unwrap_or(1)
+        .saturating_sub(1);
+    let kind = match parts.next() {
+        Some("2") => KeyEventKind::Repeat,
+        Some("3") => KeyEventKind::Release,
+        _ => KeyEventKind::Press,
+    };
+    let mut modifiers = KeyModifiers::NONE;
+    modifiers.set(KeyModifiers::SHIFT, mask & 1 != 0);
+    modifiers.set(KeyModifiers::ALT, mask & 2 != 0);
+    modifiers.set(KeyModifiers::CONTROL, mask & 4 != 0);
+    modifiers.set(KeyModifiers::SUPER, mask & 8 != 0);
+    modifiers.set(KeyModifiers::HYPER, mask & 16 != 0);
+    modifiers.set(KeyModifiers::META, mask & 32 != 0);
+    let mut state = KeyEventState::NONE;
+    state.set(KeyEventState::CAPS_LOCK, mask & 64 != 0);
+    state.set(KeyEventState::NUM_LOCK, mask & 128 != 0);
+    (modifiers, state, kind)
+}
+
+// `CSI 1 ; modifiers A`, arrows, home, end and f1 to f4 with modifiers
+fn modified_key(params: &str, last: u8) -> Option<Parsed> {
+    let code = ss3_key(last)?;
+    let (modifiers, _, kind) = modifiers_and_kind(params.split(';').nth(1));
+    key_event(code, modifiers, kind)
+}
+
+// `CSI number ; modifiers ~`
+fn special_key(params: &str) -> Option<Parsed> {
+    let mut fields = params.split(';');
+    let number = fields.next()?.parse::<u8>().ok()?;
+    let code = match number {
+        1 | 7 => KeyCode::Home,
+        2 => KeyCode::Insert,
+        3 => KeyCode::Delete,
+        4 | 8 => KeyCode::End,
+        5 => KeyCode::PageUp,
+        6 => KeyCode::PageDown,
+        n @ 11..=15 => KeyCode::F(n - 10),
+        n @ 17..=21 => KeyCode::F(n - 11),
+        n @ 23..=26 => KeyCode::F(n - 12),
+        n @ 28..=29 => KeyCode::F(n - 15),
+        n @ 31..=34 => KeyCode::F(n - 17),
+        _ => return None,
+    };
+    let (modifiers, state, kind) = modifiers_and_kind(fields.next());
+    Some(Parsed::Key(
+        KeyEvent::new_with_kind_and_state(code, modifiers, kind, state),
+        None,
+    ))
+}
+
+// `CSI code : shifted : base ; modifiers : kind ; text u`, the kitty protocol. the code is the key
+// without shift. with alternate keys on, the shifted key and the base layout key (the key in the
+// same spot on a us layout) come along, but only when they aren't the code itself (`CSI 1092::97 u`
+// is the a key on a russian layout). crossterm swaps the shifted key in and forgets shift, so
+// shift+1 would be a `!` without shift, and it drops the base layout key. this keeps the code and
+// shift and gives back the base key, which is `None` when the terminal left it out
+fn kitty_key(params: &str) -> Option<Parsed> {
+    let mut fields = params.split(';');
+    let mut codes = fields.next()?.split(':');
+    let code = codes.next()?.parse::<u32>().ok()?;
+    let base = codes
+        .nth(1)
+        .and_then(|base| base.parse::<u32>().ok())
+        .and_then(kitty_code)
+        .map(|(base, _)| base);
+    let (mut modifiers, state, kind) = modifiers_and_kind(fields.next());
+    let (code, keypad) = kitty_code(code)?;
+    // a modifier key counts as held while it is pressed, some terminals leave its own bit out
+    if let KeyCode::Modifier(modifier) = code {
+        modifiers |= match modifier {
+            ModifierKeyCode::LeftShift | ModifierKeyCode::RightShift => KeyModifiers::SHIFT,
+            ModifierKeyCode::LeftControl | ModifierKeyCode::RightControl => KeyModifiers::CONTROL,
+            ModifierKeyCode::LeftAlt | ModifierKeyCode::RightAlt => KeyModifiers::ALT,
+            ModifierKeyCode::LeftSuper | ModifierKeyCode::RightSuper => KeyModifiers::SUPER,
+            ModifierKeyCode::LeftHyper | ModifierKeyCode::RightHyper => KeyModifiers::HYPER,
+            ModifierKeyCode::LeftMeta | ModifierKeyCode::RightMeta => KeyModifiers::META,
+            _ => KeyModifiers::NONE,
+        };
+    }
+    Some(Parsed::Key(
+        KeyEvent::new_with_kind_and_state(code, modifiers, kind, keypad | state),
+        base,
+    ))
+}
+
+// keys are their unicode codepoint, or one from the private use area for keys that type nothing
+fn kitty_code(code: u32) -> Option<(KeyCode, KeyEventState)> {
+    let keypad = |code| Some((code, KeyEventState::KEYPAD));
+    let key = |code| Some((code, KeyEventState::NONE));
+    match code {
+        9 => key(KeyCode::Tab),
+        13 => key(KeyCode::Enter),
+        27 => key(KeyCode::Esc),
+        127 => key(KeyCode::Backspace),
+        57358 => key(KeyCode::CapsLock),
+        57359 => key(KeyCode::ScrollLock),
+        57360 => key(KeyCode::NumLock),
+        57361 => key(KeyCode::PrintScreen),
+        57362 => key(KeyCode::Pause),
+        57363 => key(KeyCode::Menu),
+        57376..=57398 => key(KeyCode::F((code - 57376 + 13) as u8)),
+        57399..=57408 => keypad(KeyCode::Char(char::from_digit(code - 57399, 10)?)),
+        57409 => keypad(KeyCode::Char('.')),
+        57410 => keypad(KeyCode::Char('/')),
+        57411 => keypad(KeyCode::Char('*')),
+        57412 => keypad(KeyCode::Char('-')),
+        57413 => keypad(KeyCode::Char('+')),
+        57414 => keypad(KeyCode::Enter),
+        57415 => keypad(KeyCode::Char('=')),
+        57416 => keypad(KeyCode::Char(',')),
+        57417 => keypad(KeyCode::Left),
+        57418 => keypad(KeyCode::Right),
+        57419 => keypad(KeyCode::Up),
+        57420 => keypad(KeyCode::Down),
+        57421 => keypad(KeyCode::PageUp),
+        57422 => keypad(KeyCode::PageDown),
+        57423 => keypad(KeyCode::Home),
+        57424 => keypad(KeyCode::End),
+        57425 => keypad(KeyCode::Insert),
+        57426 => keypad(KeyCode::Delete),
+        57427 => keypad(KeyCode::KeypadBegin),
+        57428..=57440 => key(KeyCode::Media(media_key(code)?)),
+        57441 => key(KeyCode::Modifier(ModifierKeyCode::LeftShift)),
+        57442 => key(KeyCode::Modifier(ModifierKeyCode::LeftControl)),
+        57443 => key(KeyCode::Modifier(ModifierKeyCode::LeftAlt)),
+        57444 => key(KeyCode::Modifier(ModifierKeyCode::LeftSuper)),
+        57445 => key(KeyCode::Modifier(ModifierKeyCode::LeftHyper)),
+        57446 => key(KeyCode::Modifier(ModifierKeyCode::LeftMeta)),
+        57447 => key(KeyCode::Modifier(ModifierKeyCode::RightShift)),
+        57448 => key(KeyCode::Modifier(ModifierKeyCode::RightControl)),
+        57449 => key(KeyCode::Modifier(ModifierKeyCode::RightAlt)),
+        57450 => key(KeyCode::Modifier(ModifierKeyCode::RightSuper)),
+        57451 => key(KeyCode::Modifier(ModifierKeyCode::RightHyper)),
+        57452 => key(KeyCode::Modifier(ModifierKeyCode::RightMeta)),
+        57453 => key(KeyCode::Modifier(ModifierKeyCode::IsoLevel3Shift)),
+        57454 => key(KeyCode::Modifier(ModifierKeyCode::IsoLevel5Shift)),
+        code => key(KeyCode::Char(char::from_u32(code)?)),
+    }
+}
+
+fn media_key(code: u32) -> Option<MediaKeyCode> {
+    Some(match code {
+        57428 => MediaKeyCode::Play,
+        57429 => MediaKeyCode::Pause,
+        57430 => MediaKeyCode::PlayPause,
+        57431 => MediaKeyCode::Reverse,
+        57432 => MediaKeyCode::Stop,
+        57433 => MediaKeyCode::FastForward,
+        57434 => MediaKeyCode::Rewind,
+        57435 => MediaKeyCode::TrackNext,
+        57436 => MediaKeyCode::TrackPrevious,
+        57437 => MediaKeyCode::Record,
+        57438 => MediaKeyCode::LowerVolume,
+        57439 => MediaKeyCode::RaiseVolume,
+        57440 => MediaKeyCode::MuteVolume,
+        _ => return None,
+    })
+}
+
+fn numbers<const N: usize>(params: &str) -> Option<[u16; N]> {
+    let mut numbers = [0; N];
+    let mut fields = params.split(';');
+    for number in numbers.iter_mut() {
+        *number = fields.next()?.parse().ok()?;
+    }
+    Some(numbers)
+}
+
+fn cursor_position(params: &str) -> Option<Parsed> {
+    let [row, column] = numbers::<2>(params)?;
+    Some(Parsed::Reply(Reply::CursorPosition {
+        column: column.saturating_sub(1),
+        row: row.saturating_sub(1),
+    }))
+}
+
+// `mode ; setting $`
+fn mode_report(params: &str) -> Option<Parsed> {
+    let [mode, setting] = numbers::<2>(params.strip_suffix('$')?)?;
+    Some(Parsed::Reply(Reply::Mode {
+        mode,
+        setting: setting as u8,
+    }))
+}
+
+// `CSI < button ; x ; y M`, or `m` for a release
+fn sgr_mouse(params: &str, release: bool) -> Option<Parsed> {
+    let [cb, cx, cy] = numbers::<3>(params)?;
+    let mut event = mouse(cb, cx, cy)?;
+    if release && let MouseEventKind::Down(button) = event.kind {
+        event.kind = MouseEventKind::Up(button);
+    }
+    Some(Parsed::Event(CEvent::Mouse(event)))
+}
+
+// `CSI button ; x ; y M`, the same numbers as the old encoding
+fn rxvt_mouse(params: &str) -> Option<Parsed> {
+    let [cb, cx, cy] = numbers::<3>(params)?;
+    mouse(cb.checked_sub(32)?, cx, cy).map(|event| Parsed::Event(CEvent::Mouse(event)))
+}
+
+// `CSI M` and 3 bytes, each 32 more than the number
+fn old_mouse(cb: u8, cx: u8, cy: u8) -> Option<Parsed> {
+    let number = |b: u8| b.checked_sub(32).map(u16::from);
+    mouse(number(cb)?, number(cx)?, number(cy)?).map(|event| Parsed::Event(CEvent::Mouse(event)))
+}
+
+// the low bits are the button, then bits for shift, alt, ctrl, moving and the wheel.
+// positions start at 1
+fn mouse(cb: u16, cx: u16, cy: u16) -> Option<MouseEvent> {
+    let button = match cb & 0b11 {
+        0 => Some(CMouseButton::Left),
+        1 => Some(CMouseButton::Middle),
+        2 => Some(CMouseButton::Right),
+        _ => None,
+    };
+    let kind = match (cb & 0b1100_0000, cb & 0b10_0000 != 0, button) {
+        (64, _, _) => match cb & 0b11 {
+            0 => MouseEventKind::ScrollUp,
+            1 => MouseEventKind::ScrollDown,
+            2 => MouseEventKind::ScrollLeft,
+            _ => MouseEventKind::ScrollRight,
+        },
+        (0, true, Some(button)) => MouseEventKind::Drag(button),
+        (0, true, None) => MouseEventKind::Moved,
+        (0, false, Some(button)) => MouseEventKind::Down(button),
+        // the old encoding doesn't say which button went up
+        (0, false, None) => MouseEventKind::Up(CMouseButton::Left),
+        _ => return None,
+    };
+    let mut modifiers = KeyModifiers::NONE;
+    modifiers.set(KeyModifiers::SHIFT, cb & 4 != 0);
+    modifiers.set(KeyModifiers::ALT, cb & 8 != 0);
+    modifiers.set(KeyModifiers::CONTROL, cb & 16 != 0);
+    Some(MouseEvent {
+        kind,
+        column: cx.saturating_sub(1),
+        row: cy.saturating_sub(1),
+        modifiers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(bytes: &[u8]) -> Parsed {
+        match parse(bytes) {
+            Parse::Done(parsed, len) => {
+                assert_eq!(len, bytes.len());
+                parsed
+            }
+            other => panic!("{:?} parsed as {:?}", bytes, other),
+        }
+    }
+
+    fn key_of(bytes: &[u8]) -> KeyEvent {
+        match parsed(bytes) {
+            Parsed::Key(event, _) => event,
+            other => panic!("{:?} parsed as {:?}", bytes, other),
+        }
+    }
+
+    #[test]
+    fn replies_to_queries() {
+        assert_eq!(
+            parsed(b"\x1b[?2026;2$y"),
+            Parsed::Reply(Reply::Mode {
+                mode: 2026,
+                setting: 2
+            })
+        );
+        assert_eq!(
+            parsed(b"\x1b[?62;22;52c"),
+            Parsed::Reply(Reply::DeviceAttributes)
+        );
+        assert_eq!(parsed(b"\x1b[?15u"), Parsed::Reply(Reply::KeyboardFlags));
+        assert_eq!(
+            parsed(b"\x1b[12;40R"),
+            Parsed::Reply(Reply::CursorPosition {
+                column: 39,
+                row: 11
+            })
+        );
+    }
+
+    #[test]
+    fn half_a_sequence_waits_for_the_rest() {
+        assert_eq!(parse(b"\x1b[?2026;"), Parse::Incomplete);
+        assert_eq!(parse(b"\xc3"), Parse::Incomplete);
+        assert_eq!(key_of(b"\x1b").code, KeyCode::Esc);
+    }
+
+    #[test]
+    fn legacy_and_kitty_keys() {
+        let up = key_of(b"\x1b[1;5A");
+        assert_eq!(
+            (up.code, up.modifiers),
+            (KeyCode::Up, KeyModifiers::CONTROL)
+        );
+        assert_eq!(key_of(b"\x1b[3~").code, KeyCode::Delete);
+        let alt_x = key_of(b"\x1bx");
+        assert_eq!(
+            (alt_x.code, alt_x.modifiers),
+            (KeyCode::Char('x'), KeyModifiers::ALT)
+        );
+        assert_eq!(key_of("é".as_bytes()).code, KeyCode::Char('é'));
+
+        // shift+ctrl+s let go
+        let s = key_of(b"\x1b[115;6:3u");
+        assert_eq!(s.code, KeyCode::Char('s'));
+        assert_eq!(s.modifiers, KeyModifiers::SHIFT | KeyModifiers::CONTROL);
+        assert_eq!(s.kind, KeyEventKind::Release);
+        let keypad = key_of(b"\x1b[57400u");
+        assert_eq!(
+            (keypad.code, keypad.state),
+            (KeyCode::Char('1'), KeyEventState::KEYPAD)
+        );
+    }
+
+    #[test]
+    fn kitty_keys_bring_the_base_layout_key() {
+        // ф is where a is on a us layout
+        let Parsed::Key(event, base) = parsed("\x1b[1092::97u".as_bytes()) else {
+            panic!("not a key");
+        };
+        assert_eq!(event.code, KeyCode::Char('ф'));
+        assert_eq!(base, Some(KeyCode::Char('a')));
+
+        // shift+1 on a us layout keeps shift and the 1, with ! as the shifted key
+        let Parsed::Key(event, base) = parsed(b"\x1b[49:33;2u") else {
+            panic!("not a key");
+        };
+        assert_eq!(
+            (event.code, event.modifiers),
+            (KeyCode::Char('1'), KeyModifiers::SHIFT)
+        );
+        assert_eq!(base, None);
+
+        // ctrl+z on azerty, released, is ctrl+w on a us layout
+        let Parsed::Key(event, base) = parsed(b"\x1b[122::119;5:3u") else {
+            panic!("not a key");
+        };
+        assert_eq!(event.code, KeyCode::Char('z'));
+        assert_eq!(event.kind, KeyEventKind::Release);
+        assert_eq!(base, Some(KeyCode::Char('w')));
+    }
+
+    #[test]
+    fn sgr_mouse_press_and_release() {
+        let event = |bytes: &[u8]| match parsed(bytes) {
+            Parsed::Event(CEvent::Mouse(event)) => (event.kind, event.column, event.row),
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(
+            event(b"\x1b[<0;10;5M"),
+            (MouseEventKind::Down(CMouseButton::Left), 9, 4)
+        );
+        assert_eq!(
+            event(b"\x1b[<2;10;5m"),
+            (MouseEventKind::Up(CMouseButton::Right), 9, 4)
+        );
+        assert_eq!(event(b"\x1b[<65;1;1M"), (MouseEventKind::ScrollDown, 0, 0));
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, IsTerminal, Read, Write, stdin};
+use std::os::fd::{AsFd, AsRawFd};
+use std::time::{Duration, Instant};
+
+use super::parse::{self, Parse, Parsed, Reply};
+
+// how long a terminal gets to answer a query
+const QUERY_TIMEOUT: Duration = Duration::from_secs(1);
+
+// reads keys, the mouse and the answers to queries from the terminal. crossterm's reader can't
+// be given questions it doesn't know the answer to, so on unix the terminal is read from here
+pub(crate) struct TerminalReader {
+    tty: File,
+    buffer: Vec<u8>,
+    // events that came while waiting for an answer, handed out first
+    pending: VecDeque<Parsed>,
+}
+
+impl TerminalReader {
+    // stdin if it is the terminal, otherwise the terminal the process runs in
+    pub(crate) fn open() -> io::Result<Self> {
+        let stdin = stdin();
+        let tty = if stdin.is_terminal() {
+            File::from(stdin.as_fd().try_clone_to_owned()?)
+        } else {
+            File::open("/dev/tty")?
+        };
+        Ok(TerminalReader {
+            tty,
+            buffer: Vec::new(),
+            pending: VecDeque::new(),
+        })
+    }
+
+    // the next key, mouse event or late answer, `None` once everything sent so far is read
+    pub(crate) fn read(&mut self) -> io::Result<Option<Parsed>> {
+        if let Some(parsed) = self.pending.pop_front() {
+            return Ok(Some(parsed));
+        }
+        self.next(Duration::ZERO)
+    }
+
+    // sends `query` and then DA1, which every terminal answers in order, so the answers that
+    // come before its one are all there will be. `None` if the terminal didn't answer in time
+    pub(crate) fn query(
+        &mut self,
+        writer: &mut impl Write,
+        query: &str,
+    ) -> io::Result<Option<Vec<Reply>>> {
+        write!(writer, "{}\x1b[c", query)?;
+        writer.flush()?;
+        let deadline = Instant::now() + QUERY_TIMEOUT;
+        let mut replies = Vec::new();
+        loop {
+            match self.next(deadline.saturating_duration_since(Instant::now()))? {
+                Some(Parsed::Reply(Reply::DeviceAttributes)) => return Ok(Some(replies)),
+                Some(Parsed::Reply(reply)) => replies.push(reply),
+                Some(event) => self.pending.push_back(event),
+                None => return Ok(None),
+            }
+        }
+    }
+
+    fn next(&mut self, timeout: Duration) -> io::Result<Option<Parsed>> {
+        let deadline = Instant::now() + timeout;
+        while self.fill(Duration::ZERO)? {}
+        loop {
+            match parse::parse(&self.buffer) {
+                Parse::Done(parsed, len) => {
+                    self.buffer.drain(..len);
+                    return Ok(Some(parsed));
+                }
+                Parse::Skip(len) => {
+                    self.buffer.drain(..len);
+                }
+                Parse::Incomplete => {
+                    if !self.fill(deadline.saturating_duration_since(Instant::now()))? {
+                        return Ok(None);
+                    }
+                    while self.fill(Duration::ZERO)? {}
+                }
+            }
+        }
+    }
+
+    // reads what the terminal sent, waiting up to `timeout` for it. false if nothing came
+    fn fill(&mut self, timeout: Duration) -> io::Result<bool> {
+        let mut fd = libc::pollfd {
+            fd: self.tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let millis = timeout.as_millis().min(i32::MAX as u128) as i32;
+        // safe: one pollfd that lives through the call
+        match unsafe { libc::poll(&mut fd, 1, millis) } {
+            0 => return Ok(false),
+            ready if ready < 0 => {
+                let error = io::Error::last_os_error();
+                // a signal (like a resize) came while waiting
+                if error.kind() == io::ErrorKind::Interrupted {
+                    return Ok(false);
+                }
+                return Err(error);
+            }
+            _ => {}
+        }
+        let mut chunk = [0; 1024];
+        let read = self.tty.read(&mut chunk)?;
+        self.buffer.extend_from_slice(&chunk[..read]);
+        Ok(read > 0)
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::{Event, Key, Modifiers};
+
+// terminals without the kitty protocol only send presses, and send them again and again
+// while a key is held (the os key repeat). a key counts as released once its presses stop.
+// the os only repeats the last key pressed, so holding two keys at once doesn't work here
+pub(crate) struct ReleaseSynthesizer {
+    held: HashMap<Key, Held>,
+    // how long to wait for the first repeat, longer than the os repeat delay
+    // (600ms on sway and kde, 660ms on xorg by default)
+    pub(crate) delay: Duration,
+    // how long to wait between repeats, longer than the os repeat interval
+    pub(crate) interval: Duration,
+}
+
+struct Held {
+    // when the key was last pressed or repeated
+    last: Instant,
+    repeated: bool,
+    scancode: Key,
+    // what it was last pressed with, the release keeps them so ctrl doesn't let go with the key
+    modifiers: Modifiers,
+}
+
+impl ReleaseSynthesizer {
+    pub(crate) fn new() -> Self {
+        ReleaseSynthesizer {
+            held: HashMap::new(),
+            delay: Duration::from_millis(800),
+            interval: Duration::from_millis(100),
+        }
+    }
+
+    // the first press is a key down, the ones after it while held are repeats
+    pub(crate) fn press(&mut self, key: Key, scancode: Key, modifiers: Modifiers) -> Event {
+        let repeated = self.held.contains_key(&key);
+        self.held.insert(
+            key,
+            Held {
+                last: Instant::now(),
+                repeated,
+                scancode,
+                modifiers,
+            },
+        );
+        if repeated {
+            Event::KeyRepeat {
+                key,
+                scancode,
+                modifiers,
+            }
+        } else {
+            Event::KeyDown {
+                key,
+                scancode,
+                modifiers,
+            }
+        }
+    }
+
+    // pushes a key up for every key that stopped repeating
+    pub(crate) fn release_expired(&mut self, events: &mut Vec<Event>) {
+        let (delay, interval) = (self.delay, self.interval);
+        self.held.retain(|&key, held| {
+            let timeout = if held.repeated { interval } else { delay };
+            if held.last.elapsed() < timeout {
+                return true;
+            }
+            events.push(Event::KeyUp {
+                key,
+                scancode: held.scancode,
+                modifiers: held.modifiers,
+            });
+            false
+        });
+    }
+
+    // when the terminal does send a release after all
+    pub(crate) fn forget(&mut self, key: Key) {
+        self.held.remove(&key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Input;
+
+    #[test]
+    fn release_keeps_the_modifiers_held_with_the_key() {
+        let mut releases = ReleaseSynthesizer::new();
+        releases.delay = Duration::ZERO;
+        let mut input = Input::new();
+        input.apply(&releases.press(Key::S, Key::S, Modifiers::CTRL));
+
+        // ctrl is still down after s stops repeating
+        let mut events = Vec::new();
+        releases.release_expired(&mut events);
+        assert_eq!(events.len(), 1);
+        for event in &events {
+            input.apply(event);
+        }
+        assert!(!input.keys().is_pressed(Key::S));
+        assert!(input.keys().modifiers().contains(Modifiers::CTRL));
+    }
+}
@@ -0,0 +1,167 @@
+use crossterm::{
+    cursor,
+    event::{DisableFocusChange, DisableMouseCapture, PopKeyboardEnhancementFlags},
+    execute,
+    style::Print,
+    terminal,
+};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write, stdout};
+use std::panic;
+use std::sync::{
+    Mutex, Once,
+    atomic::{AtomicBool, Ordering},
+};
+
+// where the game is drawn, which changes how the terminal is given back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Screen {
+    // the alternate screen, leaving it brings back what was there before
+    Fullscreen,
+    // lines of the normal screen. the cursor goes to the start of the line after `last_row`,
+    // or stays where it is if there is none (not reserved yet, or already cleared)
+    Inline { last_row: Option<u16> },
+}
+
+// what the game draws to, so the panic hook and signals know where to restore it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Output {
+    Stdout,
+    Tty,
+    // some writer only the backend has, so only its drop can restore it. never marked taken here
+    Other,
+}
+
+// set while a crossterm backend has the terminal, so it is restored exactly once:
+// by the backend's drop, the panic hook or a fatal signal, whichever comes first
+static TERMINAL_TAKEN: AtomicBool = AtomicBool::new(false);
+static SCREEN: Mutex<Screen> = Mutex::new(Screen::Fullscreen);
+static OUTPUT: Mutex<Output> = Mutex::new(Output::Stdout);
+static INSTALL: Once = Once::new();
+// set while the terminal's colors are the game's palette, so they are reset too
+static PALETTE_CHANGED: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn set_screen(screen: Screen) {
+    *SCREEN.lock().unwrap_or_else(|e| e.into_inner()) = screen;
+}
+
+pub(crate) fn screen() -> Screen {
+    *SCREEN.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub(crate) fn mark_taken(screen: Screen, output: Output) {
+    set_screen(screen);
+    *OUTPUT.lock().unwrap_or_else(|e| e.into_inner()) = output;
+    TERMINAL_TAKEN.store(true, Ordering::SeqCst);
+    INSTALL.call_once(|| {
+        install_panic_hook();
+        #[cfg(unix)]
+        install_fatal_signal_handler();
+    });
+}
+
+pub(crate) fn is_taken() -> bool {
+    TERMINAL_TAKEN.load(Ordering::SeqCst)
+}
+
+pub(crate) fn set_palette_changed() {
+    PALETTE_CHANGED.store(true, Ordering::SeqCst);
+}
+
+// true if the palette was changed, the caller has to reset it then
+pub(crate) fn take_palette_changed() -> bool {
+    PALETTE_CHANGED.swap(false, Ordering::SeqCst)
+}
+
+// true if the terminal was still taken, the caller has to restore it then
+pub(crate) fn release() -> bool {
+    TERMINAL_TAKEN.swap(false, Ordering::SeqCst)
+}
+
+// the terminal the process runs in, even when stdout is redirected
+pub(crate) fn open_tty() -> io::Result<File> {
+    #[cfg(unix)]
+    return OpenOptions::new().read(true).write(true).open("/dev/tty");
+    #[cfg(windows)]
+    return OpenOptions::new().read(true).write(true).open("CONOUT$");
+}
+
+// raw mode off, keyboard flags popped, mouse released, cursor and colors back and the main screen shown
+// (or the cursor put below the game when inline)
+pub(crate) fn restore_with(
+    writer: &mut impl Write,
+    reads_input: bool,
+    screen: Screen,
+    palette_changed: bool,
+) {
+    // nothing to do about errors here, restore as much as possible
+    if reads_input {
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(
+            writer,
+            PopKeyboardEnhancementFlags,
+            DisableMouseCapture,
+            DisableFocusChange
+        );
+    }
+    let _ = execute!(writer, cursor::Show);
+    if palette_changed {
+        // back to the terminal's own 16 colors, foreground and background
+        let _ = execute!(writer, Print("\x1b]104\x07\x1b]110\x07\x1b]111\x07"));
+    }
+    let _ = match screen {
+        Screen::Fullscreen => execute!(writer, terminal::LeaveAlternateScreen),
+        Screen::Inline {
+            last_row: Some(row),
+        } => execute!(writer, cursor::MoveTo(0, row), Print("\r\n")),
+        Screen::Inline { last_row: None } => Ok(()),
+    };
+}
+
+// restores the terminal from outside the backend (a panic or a signal).
+// does nothing if no backend has the terminal
+pub(crate) fn restore_terminal() {
+    if !release() {
+        return;
+    }
+    let screen = screen();
+    let palette_changed = take_palette_changed();
+    match *OUTPUT.lock().unwrap_or_else(|e| e.into_inner()) {
+        Output::Stdout => restore_with(&mut stdout(), true, screen, palette_changed),
+        Output::Tty => match open_tty() {
+            Ok(mut tty) => restore_with(&mut tty, true, screen, palette_changed),
+            Err(_) => {
+                let _ = terminal::disable_raw_mode();
+            }
+        },
+        Output::Other => {}
+    }
+}
+
+// the terminal is restored before the previous hook runs, so the panic message
+// ends up on the main screen with working newlines instead of being wiped with the alternate one
+fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        previous(info);
+    }));
+}
+
+// SIGQUIT kills the process without unwinding, so the terminal is restored on a thread
+// (it can't safely be done inside the handler) before dying the way it would have.
+// SIGINT, SIGTERM and SIGHUP don't kill the game, they become `Event::Quit`
+#[cfg(unix)]
+fn install_fatal_signal_handler() {
+    use signal_hook::{consts::SIGQUIT, iterator::Signals, low_level::emulate_default_handler};
+
+    let Ok(mut signals) = Signals::new([SIGQUIT]) else {
+        return;
+    };
+    std::thread::spawn(move || {
+        for signal in signals.forever() {
+            restore_terminal();
+            let _ = emulate_default_handler(signal);
+        }
+    });
+}
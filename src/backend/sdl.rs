@@ -1,91 +1,209 @@
-use super::Backend;
-use crate::{Cell, Color as CColor, Key};
+use super::{
+    Backend,
+    signals::SignalFlag,
+    style::{TextStyle, Underline},
+};
+use crate::{
+    Cell, Event as CEvent, GamepadAxis, GamepadButton, Key, Modifiers, MouseButton, Palette,
+    WoblError,
+};
 use sdl2::{
-    Sdl,
-    event::Event,
-    keyboard::Keycode,
+    EventPump, GameControllerSubsystem, Sdl, VideoSubsystem,
+    controller::{Axis, Button, GameController},
+    event::{Event, WindowEvent},
+    keyboard::{Keycode, Mod, Scancode},
+    mouse::{MouseButton as SMouseButton, MouseWheelDirection},
     pixels::Color as SColor,
     rect::Rect,
     render::{Canvas, TextureCreator},
     ttf::{Font, FontStyle, Sdl2TtfContext},
     video::{Window, WindowContext},
 };
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
-pub fn get_color(color: CColor) -> SColor {
-    match color {
-        CColor::Reset => SColor::RGB(0, 0, 0),
-        CColor::Black => SColor::RGB(0, 0, 0),
-        CColor::DarkGrey => SColor::RGB(128, 128, 128),
-        CColor::Red => SColor::RGB(255, 0, 0),
-        CColor::DarkRed => SColor::RGB(128, 0, 0),
-        CColor::Green => SColor::RGB(0, 255, 0),
-        CColor::DarkGreen => SColor::RGB(0, 128, 0),
-        CColor::Yellow => SColor::RGB(255, 255, 0),
-        CColor::DarkYellow => SColor::RGB(128, 128, 0),
-        CColor::Blue => SColor::RGB(0, 0, 255),
-        CColor::DarkBlue => SColor::RGB(0, 0, 128),
-        CColor::Magenta => SColor::RGB(255, 0, 255),
-        CColor::DarkMagenta => SColor::RGB(128, 0, 128),
-        CColor::Cyan => SColor::RGB(0, 255, 255),
-        CColor::DarkCyan => SColor::RGB(0, 128, 128),
-        CColor::White => SColor::RGB(255, 255, 255),
-        CColor::Grey => SColor::RGB(192, 192, 192),
-        CColor::Rgb { r, g, b } => SColor::RGB(r, g, b),
-        CColor::AnsiValue(v) => {
-            let r = ((v >> 5) & 0b111) * 36;
-            let g = ((v >> 2) & 0b111) * 36;
-            let b = (v & 0b11) * 85;
-            SColor::RGB(r, g, b)
+fn sdl_color((r, g, b): (u8, u8, u8)) -> SColor {
+    SColor::RGB(r, g, b)
+}
+
+// the first font with a glyph for `ch`, and whether it is a fallback.
+// the main font draws its missing glyph box if none has it
+fn font_for<'a, 'ttf>(
+    main: &'a mut Font<'ttf, 'static>,
+    fallbacks: &'a mut [Font<'ttf, 'static>],
+    ch: char,
+) -> (&'a mut Font<'ttf, 'static>, bool) {
+    if main.find_glyph(ch).is_some() {
+        return (main, false);
+    }
+    match fallbacks
+        .iter_mut()
+        .find(|font| font.find_glyph(ch).is_some())
+    {
+        Some(font) => (font, true),
+        None => (main, false),
+    }
+}
+
+// a glyph scaled down to fit the cell if it is too big, keeping its shape, and centered in it
+fn fit(width: u32, height: u32, cell: Rect) -> Rect {
+    let scale = (cell.width() as f32 / width as f32)
+        .min(cell.height() as f32 / height as f32)
+        .min(1.0);
+    let width = ((width as f32 * scale) as u32).max(1);
+    let height = ((height as f32 * scale) as u32).max(1);
+    Rect::new(
+        cell.x() + (cell.width() as i32 - width as i32) / 2,
+        cell.y() + (cell.height() as i32 - height as i32) / 2,
+        width,
+        height,
+    )
+}
+
+// halfway between two colors, dim text fades into its background
+fn blend((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> (u8, u8, u8) {
+    let mix = |a: u8, b: u8| ((a as u16 + b as u16) / 2) as u8;
+    (mix(r1, r2), mix(g1, g2), mix(b1, b2))
+}
+
+// underlines, overline and frame, in the draw color. sdl_ttf only knows plain underlines,
+// so they are all drawn here to look alike. curly ones are placed by their x on the screen
+// so the wave goes on from one cell to the next
+fn draw_lines(
+    canvas: &mut Canvas<Window>,
+    area: Rect,
+    baseline: i32,
+    style: &TextStyle,
+) -> Result<(), String> {
+    let thickness = (area.height() / 16).max(1);
+    let t = thickness as i32;
+    let bottom = area.bottom() - t;
+    let under = (baseline + t).min(bottom);
+    let line = |y: i32| Rect::new(area.x(), y, area.width(), thickness);
+
+    match style.underline {
+        Some(Underline::Single) => canvas.fill_rect(line(under))?,
+        Some(Underline::Double) => {
+            let upper = under.min(bottom - 2 * t);
+            canvas.fill_rect(line(upper))?;
+            canvas.fill_rect(line(upper + 2 * t))?;
+        }
+        Some(Underline::Curly) => {
+            let period = 4 * t;
+            let top = under.min(bottom - t);
+            for x in area.x()..area.right() {
+                let phase = x.rem_euclid(period);
+                // a triangle wave as tall as the line is thick
+                let rise = if phase < 2 * t { phase } else { period - phase };
+                canvas.fill_rect(Rect::new(x, top + rise / 2, 1, thickness))?;
+            }
+        }
+        Some(Underline::Dotted) => {
+            for x in (area.x()..area.right()).step_by(2 * thickness as usize) {
+                canvas.fill_rect(Rect::new(x, under, thickness, thickness))?;
+            }
+        }
+        Some(Underline::Dashed) => {
+            for x in (area.x()..area.right()).step_by(4 * thickness as usize) {
+                let length = (3 * t).min(area.right() - x) as u32;
+                canvas.fill_rect(Rect::new(x, under, length, thickness))?;
+            }
         }
+        None => {}
+    }
+    if style.overlined {
+        canvas.fill_rect(line(area.y()))?;
+    }
+    if style.framed {
+        canvas.draw_rect(area)?;
     }
+    Ok(())
 }
 
 pub struct SDLBackend<'ttf> {
     font_width: u32,
     font_height: u32,
-    context: Sdl,
+    video_subsystem: VideoSubsystem,
+    event_pump: EventPump,
     canvas: Option<Canvas<Window>>,
     font: Font<'ttf, 'static>,
+    // tried in order for characters `font` doesn't have
+    fallback_fonts: Vec<Font<'ttf, 'static>>,
+    ttf_context: &'ttf Sdl2TtfContext,
+    font_size: u32,
     texture_creator: Option<TextureCreator<WindowContext>>,
 
-    pressed_keys: HashSet<Key>,
-    just_pressed: HashSet<Key>,
-    released_keys: HashSet<Key>,
+    // `None` if the subsystem couldn't start, then there are just no gamepads
+    controller_subsystem: Option<GameControllerSubsystem>,
+    // open controllers by instance id, closed when dropped
+    controllers: HashMap<u32, GameController>,
 
+    quit_signals: SignalFlag,
+
+    palette: Palette,
+
+    // when the backend was made, blinking text is timed from it
+    started: Instant,
     frame_start: Instant,
     frame_millis: u64,
 }
 
 impl<'ttf> SDLBackend<'ttf> {
+    // fails with `WoblError::NoDisplay` if there is nowhere to open a window,
+    // so games can fall back to the terminal
     pub fn new(
         font_size: u32,
         font_path: &str,
         ttf_context: &'ttf Sdl2TtfContext,
         context: Sdl,
-    ) -> Self {
-        let font = ttf_context.load_font(font_path, font_size as u16).unwrap();
+    ) -> Result<Self, WoblError> {
+        let font = ttf_context
+            .load_font(font_path, font_size as u16)
+            .map_err(|e| WoblError::Font(format!("{}: {}", font_path, e)))?;
+        let (font_width, font_height) = font
+            .size_of("W")
+            .map_err(|e| WoblError::Font(e.to_string()))?;
 
-        let (font_width, font_height) = font.size_of("W").unwrap();
+        let video_subsystem = context.video().map_err(WoblError::NoDisplay)?;
+        let event_pump = context.event_pump().map_err(WoblError::Sdl)?;
+        let controller_subsystem = context.game_controller().ok();
 
-        SDLBackend {
+        Ok(SDLBackend {
             font_width,
             font_height,
-            context,
+            video_subsystem,
+            event_pump,
             canvas: None,
             font,
             texture_creator: None,
-            pressed_keys: HashSet::new(),
-            just_pressed: HashSet::new(),
-            released_keys: HashSet::new(),
+            controller_subsystem,
+            controllers: HashMap::new(),
+            quit_signals: SignalFlag::quit(),
+            palette: Palette::default(),
+            started: Instant::now(),
             frame_start: Instant::now(),
             frame_millis: 16,
-        }
+            fallback_fonts: Vec::new(),
+            ttf_context,
+            font_size,
+        })
+    }
+
+    // a font for characters the main one doesn't have (cjk, symbols, a color emoji font...).
+    // fallbacks are tried in the order they were added, and glyphs from them are
+    // scaled down and centered to fit the cell
+    pub fn add_fallback_font(&mut self, font_path: &str) -> Result<(), WoblError> {
+        let font = self
+            .ttf_context
+            .load_font(font_path, self.font_size as u16)
+            .map_err(|e| WoblError::Font(format!("{}: {}", font_path, e)))?;
+        self.fallback_fonts.push(font);
+        Ok(())
     }
 
     fn map_keycode(code: Keycode) -> Key {
         match code {
+            // Letters
             Keycode::A => Key::A,
             Keycode::B => Key::B,
             Keycode::C => Key::C,
@@ -112,76 +230,498 @@ impl<'ttf> SDLBackend<'ttf> {
             Keycode::X => Key::X,
             Keycode::Y => Key::Y,
             Keycode::Z => Key::Z,
+
+            // Digits
+            Keycode::Num0 => Key::Key0,
+            Keycode::Num1 => Key::Key1,
+            Keycode::Num2 => Key::Key2,
+            Keycode::Num3 => Key::Key3,
+            Keycode::Num4 => Key::Key4,
+            Keycode::Num5 => Key::Key5,
+            Keycode::Num6 => Key::Key6,
+            Keycode::Num7 => Key::Key7,
+            Keycode::Num8 => Key::Key8,
+            Keycode::Num9 => Key::Key9,
+
+            // Function keys
+            Keycode::F1 => Key::F1,
+            Keycode::F2 => Key::F2,
+            Keycode::F3 => Key::F3,
+            Keycode::F4 => Key::F4,
+            Keycode::F5 => Key::F5,
+            Keycode::F6 => Key::F6,
+            Keycode::F7 => Key::F7,
+            Keycode::F8 => Key::F8,
+            Keycode::F9 => Key::F9,
+            Keycode::F10 => Key::F10,
+            Keycode::F11 => Key::F11,
+            Keycode::F12 => Key::F12,
+            Keycode::F13 => Key::F13,
+            Keycode::F14 => Key::F14,
+            Keycode::F15 => Key::F15,
+            Keycode::F16 => Key::F16,
+            Keycode::F17 => Key::F17,
+            Keycode::F18 => Key::F18,
+            Keycode::F19 => Key::F19,
+            Keycode::F20 => Key::F20,
+            Keycode::F21 => Key::F21,
+            Keycode::F22 => Key::F22,
+            Keycode::F23 => Key::F23,
+            Keycode::F24 => Key::F24,
+
+            // Whitespace and control
             Keycode::Space => Key::Space,
             Keycode::Escape => Key::Escape,
-            Keycode::Return => Key::Enter,
+            Keycode::Return | Keycode::Return2 => Key::Enter,
             Keycode::Backspace => Key::Backspace,
             Keycode::Tab => Key::Tab,
+
+            // Navigation and editing
+            Keycode::Insert => Key::Insert,
+            Keycode::Delete => Key::Delete,
+            Keycode::Home => Key::Home,
+            Keycode::End => Key::End,
+            Keycode::PageUp => Key::PageUp,
+            Keycode::PageDown => Key::PageDown,
+            Keycode::PrintScreen => Key::PrintScreen,
+            Keycode::ScrollLock => Key::ScrollLock,
+            Keycode::Pause => Key::Pause,
+            Keycode::NumLockClear => Key::NumLock,
+            Keycode::Application | Keycode::Menu => Key::Menu,
+
+            // Arrows
             Keycode::Up => Key::Up,
             Keycode::Down => Key::Down,
             Keycode::Left => Key::Left,
             Keycode::Right => Key::Right,
+
+            // Modifiers
+            Keycode::LShift => Key::LeftShift,
+            Keycode::RShift => Key::RightShift,
+            Keycode::LCtrl => Key::LeftCtrl,
+            Keycode::RCtrl => Key::RightCtrl,
+            Keycode::LAlt => Key::LeftAlt,
+            Keycode::RAlt => Key::RightAlt,
+            Keycode::LGui => Key::LeftSuper,
+            Keycode::RGui => Key::RightSuper,
+            Keycode::CapsLock => Key::CapsLock,
+
+            // Numpad
+            Keycode::Kp0 => Key::Numpad0,
+            Keycode::Kp1 => Key::Numpad1,
+            Keycode::Kp2 => Key::Numpad2,
+            Keycode::Kp3 => Key::Numpad3,
+            Keycode::Kp4 => Key::Numpad4,
+            Keycode::Kp5 => Key::Numpad5,
+            Keycode::Kp6 => Key::Numpad6,
+            Keycode::Kp7 => Key::Numpad7,
+            Keycode::Kp8 => Key::Numpad8,
+            Keycode::Kp9 => Key::Numpad9,
+            Keycode::KpPlus => Key::NumpadAdd,
+            Keycode::KpMinus => Key::NumpadSubtract,
+            Keycode::KpMultiply => Key::NumpadMultiply,
+            Keycode::KpDivide => Key::NumpadDivide,
+            Keycode::KpPeriod => Key::NumpadDecimal,
+            Keycode::KpEnter => Key::NumpadEnter,
+            Keycode::KpEquals => Key::NumpadEquals,
+
+            // Symbols
+            Keycode::Minus => Key::Minus,
+            Keycode::Equals => Key::Equals,
+            Keycode::LeftBracket => Key::LeftBracket,
+            Keycode::RightBracket => Key::RightBracket,
+            Keycode::Backslash => Key::Backslash,
+            Keycode::Semicolon => Key::Semicolon,
+            Keycode::Quote => Key::Apostrophe,
+            Keycode::Backquote => Key::Grave,
+            Keycode::Comma => Key::Comma,
+            Keycode::Period => Key::Period,
+            Keycode::Slash => Key::Slash,
+            _ => Key::Unknown,
+        }
+    }
+
+    // named after the us qwerty key in the same spot, whatever the layout says
+    fn map_scancode(code: Scancode) -> Key {
+        match code {
+            // Letters
+            Scancode::A => Key::A,
+            Scancode::B => Key::B,
+            Scancode::C => Key::C,
+            Scancode::D => Key::D,
+            Scancode::E => Key::E,
+            Scancode::F => Key::F,
+            Scancode::G => Key::G,
+            Scancode::H => Key::H,
+            Scancode::I => Key::I,
+            Scancode::J => Key::J,
+            Scancode::K => Key::K,
+            Scancode::L => Key::L,
+            Scancode::M => Key::M,
+            Scancode::N => Key::N,
+            Scancode::O => Key::O,
+            Scancode::P => Key::P,
+            Scancode::Q => Key::Q,
+            Scancode::R => Key::R,
+            Scancode::S => Key::S,
+            Scancode::T => Key::T,
+            Scancode::U => Key::U,
+            Scancode::V => Key::V,
+            Scancode::W => Key::W,
+            Scancode::X => Key::X,
+            Scancode::Y => Key::Y,
+            Scancode::Z => Key::Z,
+
+            // Digits
+            Scancode::Num0 => Key::Key0,
+            Scancode::Num1 => Key::Key1,
+            Scancode::Num2 => Key::Key2,
+            Scancode::Num3 => Key::Key3,
+            Scancode::Num4 => Key::Key4,
+            Scancode::Num5 => Key::Key5,
+            Scancode::Num6 => Key::Key6,
+            Scancode::Num7 => Key::Key7,
+            Scancode::Num8 => Key::Key8,
+            Scancode::Num9 => Key::Key9,
+
+            // Function keys
+            Scancode::F1 => Key::F1,
+            Scancode::F2 => Key::F2,
+            Scancode::F3 => Key::F3,
+            Scancode::F4 => Key::F4,
+            Scancode::F5 => Key::F5,
+            Scancode::F6 => Key::F6,
+            Scancode::F7 => Key::F7,
+            Scancode::F8 => Key::F8,
+            Scancode::F9 => Key::F9,
+            Scancode::F10 => Key::F10,
+            Scancode::F11 => Key::F11,
+            Scancode::F12 => Key::F12,
+            Scancode::F13 => Key::F13,
+            Scancode::F14 => Key::F14,
+            Scancode::F15 => Key::F15,
+            Scancode::F16 => Key::F16,
+            Scancode::F17 => Key::F17,
+            Scancode::F18 => Key::F18,
+            Scancode::F19 => Key::F19,
+            Scancode::F20 => Key::F20,
+            Scancode::F21 => Key::F21,
+            Scancode::F22 => Key::F22,
+            Scancode::F23 => Key::F23,
+            Scancode::F24 => Key::F24,
+
+            // Whitespace and control
+            Scancode::Space => Key::Space,
+            Scancode::Escape => Key::Escape,
+            Scancode::Return | Scancode::Return2 => Key::Enter,
+            Scancode::Backspace => Key::Backspace,
+            Scancode::Tab => Key::Tab,
+
+            // Navigation and editing
+            Scancode::Insert => Key::Insert,
+            Scancode::Delete => Key::Delete,
+            Scancode::Home => Key::Home,
+            Scancode::End => Key::End,
+            Scancode::PageUp => Key::PageUp,
+            Scancode::PageDown => Key::PageDown,
+            Scancode::PrintScreen => Key::PrintScreen,
+            Scancode::ScrollLock => Key::ScrollLock,
+            Scancode::Pause => Key::Pause,
+            Scancode::NumLockClear => Key::NumLock,
+            Scancode::Application | Scancode::Menu => Key::Menu,
+
+            // Arrows
+            Scancode::Up => Key::Up,
+            Scancode::Down => Key::Down,
+            Scancode::Left => Key::Left,
+            Scancode::Right => Key::Right,
+
+            // Modifiers
+            Scancode::LShift => Key::LeftShift,
+            Scancode::RShift => Key::RightShift,
+            Scancode::LCtrl => Key::LeftCtrl,
+            Scancode::RCtrl => Key::RightCtrl,
+            Scancode::LAlt => Key::LeftAlt,
+            Scancode::RAlt => Key::RightAlt,
+            Scancode::LGui => Key::LeftSuper,
+            Scancode::RGui => Key::RightSuper,
+            Scancode::CapsLock => Key::CapsLock,
+
+            // Numpad
+            Scancode::Kp0 => Key::Numpad0,
+            Scancode::Kp1 => Key::Numpad1,
+            Scancode::Kp2 => Key::Numpad2,
+            Scancode::Kp3 => Key::Numpad3,
+            Scancode::Kp4 => Key::Numpad4,
+            Scancode::Kp5 => Key::Numpad5,
+            Scancode::Kp6 => Key::Numpad6,
+            Scancode::Kp7 => Key::Numpad7,
+            Scancode::Kp8 => Key::Numpad8,
+            Scancode::Kp9 => Key::Numpad9,
+            Scancode::KpPlus => Key::NumpadAdd,
+            Scancode::KpMinus => Key::NumpadSubtract,
+            Scancode::KpMultiply => Key::NumpadMultiply,
+            Scancode::KpDivide => Key::NumpadDivide,
+            Scancode::KpPeriod => Key::NumpadDecimal,
+            Scancode::KpEnter => Key::NumpadEnter,
+            Scancode::KpEquals => Key::NumpadEquals,
+
+            // Symbols
+            Scancode::Minus => Key::Minus,
+            Scancode::Equals => Key::Equals,
+            Scancode::LeftBracket => Key::LeftBracket,
+            Scancode::RightBracket => Key::RightBracket,
+            Scancode::Backslash | Scancode::NonUsHash => Key::Backslash,
+            Scancode::Semicolon => Key::Semicolon,
+            Scancode::Apostrophe => Key::Apostrophe,
+            Scancode::Grave => Key::Grave,
+            Scancode::Comma => Key::Comma,
+            Scancode::Period => Key::Period,
+            Scancode::Slash => Key::Slash,
             _ => Key::Unknown,
         }
     }
 
-    fn update_keys(&mut self) {
-        self.just_pressed.clear();
-        self.released_keys.clear();
-
-        let mut event_pump = self.context.event_pump().unwrap();
-        for event in event_pump.poll_iter() {
-            match event {
-                Event::Quit { .. } => std::process::exit(0),
-                Event::KeyDown {
-                    keycode: Some(k),
-                    repeat: false,
-                    ..
-                } => {
-                    let key = Self::map_keycode(k);
-                    if !self.pressed_keys.contains(&key) {
-                        self.just_pressed.insert(key);
+    // the symbol and the position of a key, as long as sdl knows at least one of them
+    fn map_key(keycode: Option<Keycode>, scancode: Option<Scancode>) -> Option<(Key, Key)> {
+        if keycode.is_none() && scancode.is_none() {
+            return None;
+        }
+        Some((
+            keycode.map_or(Key::Unknown, Self::map_keycode),
+            scancode.map_or(Key::Unknown, Self::map_scancode),
+        ))
+    }
+
+    fn map_mod(keymod: Mod) -> Modifiers {
+        let mut modifiers = Modifiers::NONE;
+        modifiers.set(
+            Modifiers::SHIFT,
+            keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+        );
+        modifiers.set(
+            Modifiers::CTRL,
+            keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD),
+        );
+        modifiers.set(
+            Modifiers::ALT,
+            keymod.intersects(Mod::LALTMOD | Mod::RALTMOD),
+        );
+        modifiers.set(
+            Modifiers::SUPER,
+            keymod.intersects(Mod::LGUIMOD | Mod::RGUIMOD),
+        );
+        modifiers
+    }
+
+    fn map_button(button: Button) -> GamepadButton {
+        match button {
+            Button::A => GamepadButton::A,
+            Button::B => GamepadButton::B,
+            Button::X => GamepadButton::X,
+            Button::Y => GamepadButton::Y,
+            Button::Back => GamepadButton::Back,
+            Button::Guide => GamepadButton::Guide,
+            Button::Start => GamepadButton::Start,
+            Button::LeftStick => GamepadButton::LeftStick,
+            Button::RightStick => GamepadButton::RightStick,
+            Button::LeftShoulder => GamepadButton::LeftShoulder,
+            Button::RightShoulder => GamepadButton::RightShoulder,
+            Button::DPadUp => GamepadButton::DPadUp,
+            Button::DPadDown => GamepadButton::DPadDown,
+            Button::DPadLeft => GamepadButton::DPadLeft,
+            Button::DPadRight => GamepadButton::DPadRight,
+            Button::Misc1 => GamepadButton::Misc,
+            Button::Paddle1 => GamepadButton::Paddle1,
+            Button::Paddle2 => GamepadButton::Paddle2,
+            Button::Paddle3 => GamepadButton::Paddle3,
+            Button::Paddle4 => GamepadButton::Paddle4,
+            Button::Touchpad => GamepadButton::Touchpad,
+        }
+    }
+
+    // sdl axes are i16, sticks go from -1 to 1 and triggers from 0 to 1
+    fn map_axis(axis: Axis, value: i16) -> (GamepadAxis, f32) {
+        let value = (value as f32 / i16::MAX as f32).clamp(-1.0, 1.0);
+        match axis {
+            Axis::LeftX => (GamepadAxis::LeftX, value),
+            Axis::LeftY => (GamepadAxis::LeftY, value),
+            Axis::RightX => (GamepadAxis::RightX, value),
+            Axis::RightY => (GamepadAxis::RightY, value),
+            Axis::TriggerLeft => (GamepadAxis::LeftTrigger, value.max(0.0)),
+            Axis::TriggerRight => (GamepadAxis::RightTrigger, value.max(0.0)),
+        }
+    }
+
+    // `index` is the joystick index sdl gives when a device is added,
+    // everything afterwards uses the instance id
+    fn open_controller(&mut self, index: u32) -> Option<CEvent> {
+        let controller = self.controller_subsystem.as_ref()?.open(index).ok()?;
+        let id = controller.instance_id();
+        let name = controller.name();
+        self.controllers.insert(id, controller);
+        Some(CEvent::GamepadConnected { id, name })
+    }
+
+    fn map_mouse_button(button: SMouseButton) -> Option<MouseButton> {
+        match button {
+            SMouseButton::Left => Some(MouseButton::Left),
+            SMouseButton::Right => Some(MouseButton::Right),
+            SMouseButton::Middle => Some(MouseButton::Middle),
+            SMouseButton::X1 => Some(MouseButton::X1),
+            SMouseButton::X2 => Some(MouseButton::X2),
+            SMouseButton::Unknown => None,
+        }
+    }
+
+    // maps window pixels to cells
+    fn to_cell(&self, x: i32, y: i32) -> (i32, i32) {
+        (
+            x.div_euclid(self.font_width as i32),
+            y.div_euclid(self.font_height as i32),
+        )
+    }
+
+    fn map_event(&mut self, event: Event) -> Option<CEvent> {
+        let event = match event {
+            Event::Quit { .. } => CEvent::Quit,
+            Event::KeyDown {
+                keycode,
+                scancode,
+                keymod,
+                repeat,
+                ..
+            } => {
+                let (key, scancode) = Self::map_key(keycode, scancode)?;
+                let modifiers = Self::map_mod(keymod);
+                if repeat {
+                    CEvent::KeyRepeat {
+                        key,
+                        scancode,
+                        modifiers,
+                    }
+                } else {
+                    CEvent::KeyDown {
+                        key,
+                        scancode,
+                        modifiers,
                     }
-                    self.pressed_keys.insert(key);
                 }
-                Event::KeyUp {
-                    keycode: Some(k), ..
-                } => {
-                    let key = Self::map_keycode(k);
-                    self.pressed_keys.remove(&key);
-                    self.released_keys.insert(key);
+            }
+            Event::KeyUp {
+                keycode,
+                scancode,
+                keymod,
+                ..
+            } => {
+                let (key, scancode) = Self::map_key(keycode, scancode)?;
+                CEvent::KeyUp {
+                    key,
+                    scancode,
+                    modifiers: Self::map_mod(keymod),
                 }
-                _ => {}
             }
-        }
+            Event::TextInput { text, .. } => CEvent::Text(text),
+
+            Event::ControllerDeviceAdded { which, .. } => self.open_controller(which)?,
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.controllers.remove(&which)?;
+                CEvent::GamepadDisconnected { id: which }
+            }
+            Event::ControllerButtonDown { which, button, .. } => CEvent::GamepadDown {
+                id: which,
+                button: Self::map_button(button),
+            },
+            Event::ControllerButtonUp { which, button, .. } => CEvent::GamepadUp {
+                id: which,
+                button: Self::map_button(button),
+            },
+            Event::ControllerAxisMotion {
+                which, axis, value, ..
+            } => {
+                let (axis, value) = Self::map_axis(axis, value);
+                CEvent::GamepadAxis {
+                    id: which,
+                    axis,
+                    value,
+                }
+            }
+
+            Event::MouseMotion { x, y, .. } => {
+                let (x, y) = self.to_cell(x, y);
+                CEvent::MouseMove { x, y }
+            }
+            Event::MouseButtonDown {
+                mouse_btn, x, y, ..
+            } => {
+                let (x, y) = self.to_cell(x, y);
+                CEvent::MouseDown {
+                    button: Self::map_mouse_button(mouse_btn)?,
+                    x,
+                    y,
+                }
+            }
+            Event::MouseButtonUp {
+                mouse_btn, x, y, ..
+            } => {
+                let (x, y) = self.to_cell(x, y);
+                CEvent::MouseUp {
+                    button: Self::map_mouse_button(mouse_btn)?,
+                    x,
+                    y,
+                }
+            }
+            Event::MouseWheel {
+                x, y, direction, ..
+            } => match direction {
+                MouseWheelDirection::Flipped => CEvent::MouseWheel { x: -x, y: -y },
+                _ => CEvent::MouseWheel { x, y },
+            },
+
+            Event::Window { win_event, .. } => match win_event {
+                WindowEvent::FocusGained => CEvent::FocusGained,
+                WindowEvent::FocusLost => CEvent::FocusLost,
+                WindowEvent::SizeChanged(width, height) => CEvent::Resize {
+                    width: width as u32 / self.font_width,
+                    height: height as u32 / self.font_height,
+                },
+                _ => return None,
+            },
+            _ => return None,
+        };
+        Some(event)
     }
 }
 
 impl<'ttf> Backend for SDLBackend<'ttf> {
-    fn init(&mut self, name: &str, width: u32, height: u32) {
-        let video_subsystem = self.context.video().unwrap();
-        let window = video_subsystem
+    fn init(&mut self, name: &str, width: u32, height: u32) -> Result<(), WoblError> {
+        let window = self
+            .video_subsystem
             .window(name, width * self.font_width, height * self.font_height)
             .position_centered()
             .build()
-            .unwrap();
+            .map_err(|e| WoblError::NoDisplay(e.to_string()))?;
+        self.video_subsystem.text_input().start();
+
+        // smooth scaling for the glyphs that are fit into a cell (big color emoji)
+        sdl2::hint::set("SDL_RENDER_SCALE_QUALITY", "linear");
 
         let canvas = window
             .into_canvas()
             .accelerated()
             .present_vsync()
             .build()
-            .unwrap();
+            .map_err(|e| WoblError::Sdl(e.to_string()))?;
 
         self.texture_creator = Some(canvas.texture_creator());
         self.canvas = Some(canvas);
+        Ok(())
     }
 
     fn wait_frame(&mut self) {
         if let Some(canvas) = &mut self.canvas {
             canvas.clear();
         }
-        self.update_keys();
         let elapsed = self.frame_start.elapsed();
         let target = Duration::from_millis(self.frame_millis);
         if elapsed < target {
@@ -194,66 +734,93 @@ impl<'ttf> Backend for SDLBackend<'ttf> {
         self.frame_millis = fps.map(|f| (1000 / f) as u64).unwrap_or(16);
     }
 
-    fn is_key_pressed(&self, key: Key) -> bool {
-        self.pressed_keys.contains(&key)
+    fn set_palette(&mut self, palette: &Palette) {
+        self.palette = *palette;
     }
 
-    fn is_key_just_pressed(&self, key: Key) -> bool {
-        self.just_pressed.contains(&key)
+    fn poll_events(&mut self, events: &mut Vec<CEvent>) -> Result<(), WoblError> {
+        if self.quit_signals.take() {
+            events.push(CEvent::Quit);
+        }
+        // collected first, mapping needs `self` for the controllers
+        let sdl_events: Vec<Event> = self.event_pump.poll_iter().collect();
+        for event in sdl_events {
+            if let Some(event) = self.map_event(event) {
+                events.push(event);
+            }
+        }
+        Ok(())
     }
 
-    fn is_key_just_released(&self, key: Key) -> bool {
-        self.released_keys.contains(&key)
-    }
+    fn draw_cell(&mut self, x: u32, y: u32, cell: &Cell) -> Result<(), WoblError> {
+        let Some(canvas) = &mut self.canvas else {
+            return Ok(());
+        };
+        let style = TextStyle::from_attributes(&cell.atr);
+        let mut fg = self.palette.foreground_rgb(cell.fg);
+        let mut bg = self.palette.background_rgb(cell.bg);
+        if style.reverse {
+            std::mem::swap(&mut fg, &mut bg);
+        }
+        if style.dim {
+            fg = blend(fg, bg);
+        }
 
-    fn draw_cell(&mut self, x: u32, y: u32, cell: &Cell) {
-        if let Some(canvas) = &mut self.canvas {
-            canvas.set_draw_color(get_color(cell.bg));
-            canvas
-                .fill_rect(Rect::new(
-                    (x * self.font_width) as i32,
-                    (y * self.font_height) as i32,
-                    self.font_width,
-                    self.font_height,
-                ))
-                .unwrap();
-
-            if let Some(tc) = &self.texture_creator {
-                //set font style
-                let mut style = FontStyle::NORMAL;
-                for &attr in &cell.atr {
-                    match attr {
-                        crate::Attribute::Bold => style |= FontStyle::BOLD,
-                        crate::Attribute::Italic => style |= FontStyle::ITALIC,
-                        crate::Attribute::Underlined => style |= FontStyle::UNDERLINE,
-                        _ => (),
-                    }
-                }
-                self.font.set_style(style);
+        let area = Rect::new(
+            (x * self.font_width) as i32,
+            (y * self.font_height) as i32,
+            self.font_width,
+            self.font_height,
+        );
+        canvas.set_draw_color(sdl_color(bg));
+        canvas.fill_rect(area).map_err(WoblError::Sdl)?;
 
-                let surface = self
-                    .font
-                    .render(&cell.ch.to_string())
-                    .blended(get_color(cell.fg))
-                    .unwrap();
+        // blinking goes by the frame clock, so a whole frame blinks together
+        let elapsed = self.frame_start.duration_since(self.started);
+        if style.hidden || !style.is_blinked_on(elapsed) {
+            return Ok(());
+        }
 
-                let (glyph_width, glyph_height) = surface.size();
-                let texture = tc.create_texture_from_surface(&surface).unwrap();
+        if let Some(tc) = &self.texture_creator {
+            let (font, fallback) = font_for(&mut self.font, &mut self.fallback_fonts, cell.ch);
+            let mut font_style = FontStyle::NORMAL;
+            font_style.set(FontStyle::BOLD, style.bold);
+            font_style.set(FontStyle::ITALIC, style.italic);
+            font_style.set(FontStyle::STRIKETHROUGH, style.crossed_out);
+            font.set_style(font_style);
 
-                let target = Rect::new(
-                    (x * self.font_width) as i32,
-                    (y * self.font_height) as i32,
-                    glyph_width,
-                    glyph_height,
-                );
+            // color glyphs (emoji) come out of blended rendering in their own colors, fg only
+            // applies to the plain ones
+            let surface = font
+                .render_char(cell.ch)
+                .blended(sdl_color(fg))
+                .map_err(|e| WoblError::Font(e.to_string()))?;
 
-                canvas.copy(&texture, None, Some(target)).unwrap();
-            }
+            let (glyph_width, glyph_height) = surface.size();
+            let texture = tc
+                .create_texture_from_surface(&surface)
+                .map_err(|e| WoblError::Sdl(e.to_string()))?;
+
+            // glyphs of the main font are drawn as they are, italics can hang over a bit.
+            // wide ones (cjk, emoji) and any from other fonts are fit into the cell
+            let target = if fallback || glyph_width > area.width() * 3 / 2 {
+                fit(glyph_width, glyph_height, area)
+            } else {
+                Rect::new(area.x(), area.y(), glyph_width, glyph_height)
+            };
+            canvas
+                .copy(&texture, None, Some(target))
+                .map_err(WoblError::Sdl)?;
         }
+
+        canvas.set_draw_color(sdl_color(fg));
+        draw_lines(canvas, area, area.y() + self.font.ascent(), &style).map_err(WoblError::Sdl)
     }
-    fn flush(&mut self) {
+
+    fn flush(&mut self) -> Result<(), WoblError> {
         if let Some(canvas) = &mut self.canvas {
             canvas.present();
         }
+        Ok(())
     }
 }
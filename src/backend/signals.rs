@@ -0,0 +1,108 @@
+use signal_hook::SigId;
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+#[cfg(unix)]
+use signal_hook::consts::{SIGCONT, SIGHUP, SIGINT, SIGTERM, SIGTSTP, SIGWINCH};
+#[cfg(windows)]
+use signal_hook::consts::{SIGINT, SIGTERM};
+
+// how many flags listen to each signal. signal-hook never takes its own handler away, so a signal
+// nobody listens to anymore would just be swallowed: each one gets a handler of ours that does
+// what the signal would have done without wobl (quit, stop...) while its count is 0
+static LISTENERS: Mutex<Vec<(i32, Arc<AtomicUsize>)>> = Mutex::new(Vec::new());
+
+fn listeners(signal: i32) -> Option<Arc<AtomicUsize>> {
+    let mut listeners = LISTENERS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((_, count)) = listeners.iter().find(|(s, _)| *s == signal) {
+        return Some(count.clone());
+    }
+    let count = Arc::new(AtomicUsize::new(0));
+    let in_handler = count.clone();
+    // safe: an atomic load and emulate_default_handler are all async-signal-safe
+    unsafe {
+        signal_hook::low_level::register(signal, move || {
+            if in_handler.load(Ordering::SeqCst) == 0 {
+                let _ = signal_hook::low_level::emulate_default_handler(signal);
+            }
+        })
+    }
+    .ok()?;
+    listeners.push((signal, count.clone()));
+    Some(count)
+}
+
+// a flag set by any of a few signals, so the backends can handle them on the next poll
+// instead of inside the signal handler
+pub(crate) struct SignalFlag {
+    flag: Arc<AtomicBool>,
+    ids: Vec<(SigId, Arc<AtomicUsize>)>,
+}
+
+impl SignalFlag {
+    fn register(signals: &[i32]) -> Self {
+        let flag = Arc::new(AtomicBool::new(false));
+        // if a signal can't be registered the game just doesn't hear about it
+        let ids = signals
+            .iter()
+            .filter_map(|&signal| {
+                let count = listeners(signal)?;
+                let id = signal_hook::flag::register(signal, flag.clone()).ok()?;
+                count.fetch_add(1, Ordering::SeqCst);
+                Some((id, count))
+            })
+            .collect();
+        SignalFlag { flag, ids }
+    }
+
+    // signals that ask the game to quit: ctrl-c / kill (SIGINT, SIGTERM) and a closed terminal (SIGHUP).
+    // they become a `Event::Quit`
+    pub(crate) fn quit() -> Self {
+        #[cfg(unix)]
+        return Self::register(&[SIGINT, SIGTERM, SIGHUP]);
+        #[cfg(windows)]
+        return Self::register(&[SIGINT, SIGTERM]);
+    }
+
+    // never set, for backends that don't own the terminal the signals are about
+    pub(crate) fn none() -> Self {
+        Self::register(&[])
+    }
+
+    // someone asked to stop the game (SIGTSTP, the shell's ctrl-z). catching it means the
+    // process isn't stopped until the backend has given the terminal back
+    #[cfg(unix)]
+    pub(crate) fn suspend() -> Self {
+        Self::register(&[SIGTSTP])
+    }
+
+    // the game was continued after being stopped, by us or by a SIGSTOP
+    #[cfg(unix)]
+    pub(crate) fn resume() -> Self {
+        Self::register(&[SIGCONT])
+    }
+
+    // the terminal was resized. crossterm watches for this itself, but only in its own reader
+    #[cfg(unix)]
+    pub(crate) fn resize() -> Self {
+        Self::register(&[SIGWINCH])
+    }
+
+    // true once per batch of signals
+    pub(crate) fn take(&self) -> bool {
+        self.flag.swap(false, Ordering::SeqCst)
+    }
+}
+
+// once no backend listens to a signal it does its default thing again (see `listeners`),
+// so ctrl-c quits and ctrl-z stops the program after the backend is gone
+impl Drop for SignalFlag {
+    fn drop(&mut self) {
+        for (id, count) in self.ids.drain(..) {
+            count.fetch_sub(1, Ordering::SeqCst);
+            signal_hook::low_level::unregister(id);
+        }
+    }
+}
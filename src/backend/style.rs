@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use crate::Attribute;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Underline {
+    Single,
+    Double,
+    Curly,
+    Dotted,
+    Dashed,
+}
+
+// what the attributes of a cell add up to, for backends that draw the text themselves.
+// they are applied in order like a terminal does, so `Reset` or `NoBold` undo earlier ones
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct TextStyle {
+    pub(crate) bold: bool,
+    pub(crate) dim: bool,
+    pub(crate) italic: bool,
+    pub(crate) underline: Option<Underline>,
+    // how long the text is shown, then hidden, while blinking
+    pub(crate) blink: Option<Duration>,
+    pub(crate) reverse: bool,
+    pub(crate) hidden: bool,
+    pub(crate) crossed_out: bool,
+    pub(crate) overlined: bool,
+    pub(crate) framed: bool,
+}
+
+impl TextStyle {
+    pub(crate) fn from_attributes(attributes: &[Attribute]) -> Self {
+        let mut style = TextStyle::default();
+        for &attribute in attributes {
+            match attribute {
+                Attribute::Reset => style = TextStyle::default(),
+                Attribute::Bold => style.bold = true,
+                Attribute::Dim => style.dim = true,
+                Attribute::NoBold => style.bold = false,
+                Attribute::NormalIntensity => {
+                    style.bold = false;
+                    style.dim = false;
+                }
+                Attribute::Italic => style.italic = true,
+                Attribute::NoItalic => style.italic = false,
+                Attribute::Underlined => style.underline = Some(Underline::Single),
+                Attribute::DoubleUnderlined => style.underline = Some(Underline::Double),
+                Attribute::Undercurled => style.underline = Some(Underline::Curly),
+                Attribute::Underdotted => style.underline = Some(Underline::Dotted),
+                Attribute::Underdashed => style.underline = Some(Underline::Dashed),
+                Attribute::NoUnderline => style.underline = None,
+                Attribute::SlowBlink => style.blink = Some(Duration::from_millis(500)),
+                Attribute::RapidBlink => style.blink = Some(Duration::from_millis(150)),
+                Attribute::NoBlink => style.blink = None,
+                Attribute::Reverse => style.reverse = true,
+                Attribute::NoReverse => style.reverse = false,
+                Attribute::Hidden => style.hidden = true,
+                Attribute::NoHidden => style.hidden = false,
+                Attribute::CrossedOut => style.crossed_out = true,
+                Attribute::NotCrossedOut => style.crossed_out = false,
+                Attribute::OverLined => style.overlined = true,
+                Attribute::NotOverLined => style.overlined = false,
+                // no circles, a frame will do
+                Attribute::Framed | Attribute::Encircled => style.framed = true,
+                Attribute::NotFramedOrEncircled => style.framed = false,
+                // fraktur needs a fraktur font
+                _ => {}
+            }
+        }
+        style
+    }
+
+    // false during the hidden half of a blink, `elapsed` is the time since the backend started
+    pub(crate) fn is_blinked_on(&self, elapsed: Duration) -> bool {
+        match self.blink {
+            Some(half) => (elapsed.as_millis() / half.as_millis()).is_multiple_of(2),
+            None => true,
+        }
+    }
+}
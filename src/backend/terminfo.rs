@@ -0,0 +1,199 @@
+use crate::ColorDepth;
+use std::env;
+use std::path::PathBuf;
+
+// guesses what the terminal can show, from the most to the least reliable hint:
+// WOBL_COLOR_DEPTH, COLORTERM, then the name in TERM, then the `colors` entry of its terminfo description
+pub(crate) fn detect_color_depth() -> ColorDepth {
+    // lets players fix a wrong guess without the game having a setting for it
+    match env::var("WOBL_COLOR_DEPTH").as_deref() {
+        Ok("truecolor" | "24bit") => return ColorDepth::TrueColor,
+        Ok("256") => return ColorDepth::Ansi256,
+        Ok("16") => return ColorDepth::Ansi16,
+        _ => {}
+    }
+    if let Ok(colorterm) = env::var("COLORTERM")
+        && matches!(colorterm.as_str(), "truecolor" | "24bit")
+    {
+        return ColorDepth::TrueColor;
+    }
+    // windows terminal doesn't set TERM, and neither does the console that supports rgb since windows 10
+    if cfg!(windows) || env::var_os("WT_SESSION").is_some() {
+        return ColorDepth::TrueColor;
+    }
+
+    let Ok(term) = env::var("TERM") else {
+        return ColorDepth::Ansi16;
+    };
+    if term.ends_with("-direct") || term.contains("truecolor") || term.contains("24bit") {
+        return ColorDepth::TrueColor;
+    }
+    if term.contains("256color") {
+        return ColorDepth::Ansi256;
+    }
+
+    match terminfo_colors(&term) {
+        Some(colors) if colors >= 1 << 24 => ColorDepth::TrueColor,
+        Some(colors) if colors >= 256 => ColorDepth::Ansi256,
+        _ => ColorDepth::Ansi16,
+    }
+}
+
+// terminals known to support synchronized updates (mode 2026). only a guess for when the
+// terminal can't be asked (drawing to a writer, or on windows), it is asked when it can
+pub(crate) fn detect_synchronized_output() -> bool {
+    let term = env::var("TERM").unwrap_or_default();
+    let term_program = env::var("TERM_PROGRAM").unwrap_or_default();
+    [
+        "xterm-kitty",
+        "foot",
+        "alacritty",
+        "xterm-ghostty",
+        "contour",
+        "wezterm",
+    ]
+    .iter()
+    .any(|name| term.starts_with(name))
+        || matches!(
+            term_program.as_str(),
+            "WezTerm" | "iTerm.app" | "ghostty" | "contour"
+        )
+        || env::var_os("KITTY_WINDOW_ID").is_some()
+        || env::var_os("WEZTERM_EXECUTABLE").is_some()
+}
+
+// where ncurses looks for descriptions, in order
+fn terminfo_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(dir) = env::var_os("TERMINFO") {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Some(home) = env::var_os("HOME") {
+        dirs.push(PathBuf::from(home).join(".terminfo"));
+    }
+    if let Ok(list) = env::var("TERMINFO_DIRS") {
+        dirs.extend(list.split(':').filter(|d| !d.is_empty()).map(PathBuf::from));
+    }
+    for dir in ["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo"] {
+        dirs.push(PathBuf::from(dir));
+    }
+    dirs
+}
+
+// the `colors` number of a compiled terminfo description, see term(5)
+fn terminfo_colors(term: &str) -> Option<u32> {
+    let first = term.chars().next()?;
+    let data = terminfo_dirs().into_iter().find_map(|dir| {
+        // linux uses the first letter as the directory, macos its hex value
+        std::fs::read(dir.join(first.to_string()).join(term))
+            .or_else(|_| std::fs::read(dir.join(format!("{:x}", first as u32)).join(term)))
+            .ok()
+    })?;
+    parse_colors(&data)
+}
+
+fn parse_colors(data: &[u8]) -> Option<u32> {
+    let read_u16 = |at: usize| -> Option<usize> {
+        Some(u16::from_le_bytes([*data.get(at)?, *data.get(at + 1)?]) as usize)
+    };
+    // the newer format has 32 bit numbers so direct color fits
+    let number_size = match read_u16(0)? {
+        0o432 => 2,
+        0o1036 => 4,
+        _ => return None,
+    };
+    let names_size = read_u16(2)?;
+    let bools_count = read_u16(4)?;
+    let numbers_count = read_u16(6)?;
+
+    // `colors` is the 14th number
+    const COLORS: usize = 13;
+    if numbers_count <= COLORS {
+        return None;
+    }
+    let mut numbers_start = 12 + names_size + bools_count;
+    // numbers start on an even byte
+    numbers_start += numbers_start % 2;
+    let at = numbers_start + COLORS * number_size;
+    let bytes = data.get(at..at + number_size)?;
+
+    let colors = if number_size == 2 {
+        i16::from_le_bytes([bytes[0], bytes[1]]) as i32
+    } else {
+        i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    };
+    // negative means absent
+    u32::try_from(colors).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a compiled description with a name, some booleans and these numbers, no strings
+    fn compiled(magic: u16, names: &str, bools: usize, numbers: &[i32]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let names_size = names.len() + 1;
+        for field in [magic as usize, names_size, bools, numbers.len(), 0, 0] {
+            data.extend((field as u16).to_le_bytes());
+        }
+        data.extend(names.as_bytes());
+        data.push(0);
+        data.extend(std::iter::repeat_n(1, bools));
+        if data.len() % 2 == 1 {
+            data.push(0);
+        }
+        for &number in numbers {
+            if magic == 0o432 {
+                data.extend((number as i16).to_le_bytes());
+            } else {
+                data.extend(number.to_le_bytes());
+            }
+        }
+        data
+    }
+
+    // `colors` is the 14th number
+    fn numbers_with_colors(colors: i32) -> Vec<i32> {
+        let mut numbers = vec![-1; 15];
+        numbers[13] = colors;
+        numbers
+    }
+
+    #[test]
+    fn reads_colors_from_the_legacy_format() {
+        let data = compiled(0o432, "xterm-256color|xterm", 38, &numbers_with_colors(256));
+        assert_eq!(parse_colors(&data), Some(256));
+    }
+
+    #[test]
+    fn reads_direct_color_from_the_extended_format() {
+        let data = compiled(0o1036, "xterm-direct", 37, &numbers_with_colors(1 << 24));
+        assert_eq!(parse_colors(&data), Some(1 << 24));
+    }
+
+    #[test]
+    fn numbers_start_on_an_even_byte() {
+        // 12 byte header, 3 byte name and 2 booleans leave the numbers on an odd byte
+        let data = compiled(0o432, "ab", 2, &numbers_with_colors(8));
+        assert_eq!(parse_colors(&data), Some(8));
+    }
+
+    #[test]
+    fn missing_or_broken_colors_are_none() {
+        assert_eq!(
+            parse_colors(&compiled(0o432, "dumb", 2, &numbers_with_colors(-1))),
+            None
+        );
+        assert_eq!(parse_colors(&compiled(0o432, "short", 2, &[80, 24])), None);
+        assert_eq!(
+            parse_colors(&compiled(0o433, "bad", 2, &numbers_with_colors(8))),
+            None
+        );
+
+        let mut truncated = compiled(0o1036, "cut", 2, &numbers_with_colors(256));
+        truncated.truncate(truncated.len() - 6);
+        assert_eq!(parse_colors(&truncated), None);
+        assert_eq!(parse_colors(&[]), None);
+    }
+}
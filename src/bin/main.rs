@@ -1,31 +1,45 @@
-use wobl::{Attribute, Color, Key, Wobl, backend};
+use wobl::{Actions, Attribute, Color, Key, Wobl, WoblError, backend};
 
-fn main() {
-    let backend = Box::new(backend::CrosstermBackend::new());
-    let mut wobl = Wobl::new(backend, "wobl", 50, 25, Some(30));
+fn main() -> Result<(), WoblError> {
+    let backend = Box::new(backend::CrosstermBackend::new()?);
+    let mut wobl = Wobl::new(backend, "wobl", 50, 25, Some(30))?;
+
+    let mut actions = Actions::new();
+    actions.bind("quit", Key::Q);
+    actions.bind("up", Key::W);
+    actions.bind("up", Key::Up);
+    actions.bind("down", Key::S);
+    actions.bind("down", Key::Down);
+    actions.bind("left", Key::A);
+    actions.bind("left", Key::Left);
+    actions.bind("right", Key::D);
+    actions.bind("right", Key::Right);
+    // players can remap in bindings.toml, like `left = ["Q", "Left"]` for azerty
+    let _ = actions.load_over("bindings.toml");
+    wobl.set_actions(actions);
 
     let mut x = 10.0;
     let mut y = 10.0;
 
     loop {
-        wobl.wait_frame();
+        wobl.wait_frame()?;
         wobl.clear();
 
-        if wobl.is_key_pressed(Key::Q) {
+        if wobl.is_action_pressed("quit") || wobl.quit_requested() {
             break;
         }
 
-        if wobl.is_key_pressed(Key::W) || wobl.is_key_pressed(Key::Up) {
+        if wobl.is_action_pressed("up") {
             y -= 0.4;
         }
-        if wobl.is_key_pressed(Key::S) || wobl.is_key_pressed(Key::Down) {
+        if wobl.is_action_pressed("down") {
             y += 0.4;
         }
 
-        if wobl.is_key_pressed(Key::A) || wobl.is_key_pressed(Key::Left) {
+        if wobl.is_action_pressed("left") {
             x -= 0.8;
         }
-        if wobl.is_key_pressed(Key::D) || wobl.is_key_pressed(Key::Right) {
+        if wobl.is_action_pressed("right") {
             x += 0.8;
         }
 
@@ -35,7 +49,7 @@ fn main() {
             "hello",
             Color::Black,
             Color::White,
-            &vec![Attribute::Italic],
+            &[Attribute::Italic],
         );
 
         wobl.draw_text_atr(
@@ -44,7 +58,8 @@ fn main() {
             "world",
             Color::Red,
             Color::White,
-            &vec![Attribute::Bold],
+            &[Attribute::Bold],
         );
     }
+    Ok(())
 }
@@ -0,0 +1,331 @@
+use std::collections::HashSet;
+
+use crate::{Actions, Binding, Input, Key, MouseButton};
+
+// what a context lets through to the contexts below it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextMode {
+    // nothing, like a pause menu or a dialog over the game
+    Modal,
+    // everything but its own keys and buttons, like a hud that only takes the mouse
+    PassThrough,
+}
+
+// one layer of input, like "gameplay", "inventory" or "pause". the top context gets
+// every input, the ones below only what the contexts above let through
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputContext {
+    name: String,
+    mode: ContextMode,
+    keys: HashSet<Key>,
+    buttons: HashSet<MouseButton>,
+}
+
+impl InputContext {
+    pub fn modal(name: &str) -> Self {
+        Self::new(name, ContextMode::Modal)
+    }
+
+    pub fn pass_through(name: &str) -> Self {
+        Self::new(name, ContextMode::PassThrough)
+    }
+
+    fn new(name: &str, mode: ContextMode) -> Self {
+        InputContext {
+            name: name.to_string(),
+            mode,
+            keys: HashSet::new(),
+            buttons: HashSet::new(),
+        }
+    }
+
+    // keeps an input from the contexts below, a combo takes its key with any modifiers
+    pub fn consume(mut self, binding: impl Into<Binding>) -> Self {
+        match binding.into() {
+            Binding::Key(key) => self.keys.insert(key),
+            Binding::Combo(combo) => self.keys.insert(combo.key),
+            Binding::Mouse(button) => self.buttons.insert(button),
+        };
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn mode(&self) -> ContextMode {
+        self.mode
+    }
+
+    fn lets_through(&self, binding: Binding) -> bool {
+        match (self.mode, binding) {
+            (ContextMode::Modal, _) => false,
+            (ContextMode::PassThrough, Binding::Key(key)) => !self.keys.contains(&key),
+            (ContextMode::PassThrough, Binding::Combo(combo)) => !self.keys.contains(&combo.key),
+            (ContextMode::PassThrough, Binding::Mouse(button)) => !self.buttons.contains(&button),
+        }
+    }
+}
+
+// the contexts, bottom first
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ContextStack {
+    // each context and whether it was pushed this frame
+    contexts: Vec<(InputContext, bool)>,
+    // contexts removed this frame still hold on to their input until the next one,
+    // so the enter that closes a menu doesn't fire the weapon under it. each with the
+    // place it had on the stack, only the contexts that were under it are blocked
+    closed: Vec<(usize, InputContext)>,
+}
+
+impl ContextStack {
+    // to be called at the start of a frame
+    pub(crate) fn begin_frame(&mut self) {
+        self.closed.clear();
+        for (_, pushed) in &mut self.contexts {
+            *pushed = false;
+        }
+    }
+
+    pub(crate) fn push(&mut self, context: InputContext) {
+        self.contexts.push((context, true));
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<InputContext> {
+        let (context, _) = self.contexts.pop()?;
+        self.close(self.contexts.len(), context.clone());
+        Some(context)
+    }
+
+    pub(crate) fn remove(&mut self, name: &str) {
+        for (context, pushed) in std::mem::take(&mut self.contexts) {
+            if context.name == name {
+                self.close(self.contexts.len(), context);
+            } else {
+                self.contexts.push((context, pushed));
+            }
+        }
+    }
+
+    // the contexts above `place` move down one, and so do the places of the ones closed above it
+    fn close(&mut self, place: usize, context: InputContext) {
+        for (closed_place, _) in &mut self.closed {
+            if *closed_place > place {
+                *closed_place -= 1;
+            }
+        }
+        self.closed.push((place, context));
+    }
+
+    pub(crate) fn top(&self) -> Option<&str> {
+        self.contexts.last().map(|(c, _)| c.name())
+    }
+
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        self.contexts.iter().any(|(c, _)| c.name == name)
+    }
+
+    // whether `name` sees an input. `None` is for input no context can consume (text, the wheel).
+    // a context pushed this frame sees nothing yet, the key that opened it was meant for
+    // the context under it
+    fn sees(&self, name: &str, binding: Option<Binding>) -> bool {
+        let Some(index) = self.contexts.iter().position(|(c, _)| c.name == name) else {
+            return false;
+        };
+        if self.contexts[index].1 {
+            return false;
+        }
+        let closed_above = self
+            .closed
+            .iter()
+            .filter(|(place, _)| *place > index)
+            .map(|(_, c)| c);
+        self.contexts[index + 1..]
+            .iter()
+            .map(|(c, _)| c)
+            .chain(closed_above)
+            .all(|c| match binding {
+                Some(binding) => c.lets_through(binding),
+                None => c.mode == ContextMode::PassThrough,
+            })
+    }
+}
+
+// the input as one context sees it, from `Wobl::context`
+pub struct ContextInput<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) stack: &'a ContextStack,
+    pub(crate) input: &'a Input,
+    pub(crate) actions: &'a Actions,
+}
+
+impl ContextInput<'_> {
+    // false if this context isn't on the stack, was just pushed or is under a modal one
+    pub fn is_active(&self) -> bool {
+        self.stack.sees(self.name, None)
+    }
+
+    fn sees(&self, binding: impl Into<Binding>) -> bool {
+        self.stack.sees(self.name, Some(binding.into()))
+    }
+
+    pub fn is_key_pressed(&self, key: Key) -> bool {
+        self.sees(key) && self.input.keys().is_pressed(key)
+    }
+
+    pub fn is_key_just_pressed(&self, key: Key) -> bool {
+        self.sees(key) && self.input.keys().is_just_pressed(key)
+    }
+
+    pub fn is_key_just_released(&self, key: Key) -> bool {
+        self.sees(key) && self.input.keys().is_just_released(key)
+    }
+
+    pub fn is_key_repeated(&self, key: Key) -> bool {
+        self.sees(key) && self.input.keys().is_repeated(key)
+    }
+
+    pub fn is_mouse_pressed(&self, button: MouseButton) -> bool {
+        self.sees(button) && self.input.mouse().is_pressed(button)
+    }
+
+    pub fn is_mouse_just_pressed(&self, button: MouseButton) -> bool {
+        self.sees(button) && self.input.mouse().is_just_pressed(button)
+    }
+
+    pub fn is_mouse_just_released(&self, button: MouseButton) -> bool {
+        self.sees(button) && self.input.mouse().is_just_released(button)
+    }
+
+    // the wheel and typed text only reach contexts with nothing modal above them
+    pub fn mouse_wheel(&self) -> (i32, i32) {
+        if self.is_active() {
+            self.input.mouse().wheel()
+        } else {
+            (0, 0)
+        }
+    }
+
+    pub fn typed_text(&self) -> &str {
+        if self.is_active() {
+            self.input.text()
+        } else {
+            ""
+        }
+    }
+
+    // actions only count the bindings this context sees
+    pub fn is_action_pressed(&self, action: &str) -> bool {
+        self.visible_bindings(action)
+            .any(|b| b.is_pressed(self.input.keys(), self.input.mouse()))
+    }
+
+    pub fn is_action_just_pressed(&self, action: &str) -> bool {
+        self.visible_bindings(action)
+            .any(|b| b.is_just_pressed(self.input.keys(), self.input.mouse()))
+    }
+
+    pub fn is_action_just_released(&self, action: &str) -> bool {
+        self.visible_bindings(action)
+            .any(|b| b.is_just_released(self.input.keys(), self.input.mouse()))
+            && !self.is_action_pressed(action)
+    }
+
+    fn visible_bindings(&self, action: &str) -> impl Iterator<Item = &Binding> {
+        self.actions
+            .bindings(action)
+            .iter()
+            .filter(|b| self.sees(**b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // contexts pushed on an earlier frame, so they can already see input
+    fn stack(contexts: &[InputContext]) -> ContextStack {
+        let mut stack = ContextStack::default();
+        for context in contexts {
+            stack.push(context.clone());
+        }
+        stack.begin_frame();
+        stack
+    }
+
+    fn sees_key(stack: &ContextStack, name: &str, key: Key) -> bool {
+        stack.sees(name, Some(Binding::Key(key)))
+    }
+
+    #[test]
+    fn modal_hides_everything_below() {
+        let stack = stack(&[InputContext::modal("game"), InputContext::modal("pause")]);
+        assert!(sees_key(&stack, "pause", Key::Enter));
+        assert!(stack.sees("pause", None));
+        assert!(!sees_key(&stack, "game", Key::Enter));
+        assert!(!stack.sees("game", None));
+        assert!(!sees_key(&stack, "menu", Key::Enter));
+    }
+
+    #[test]
+    fn pass_through_only_keeps_what_it_consumes() {
+        let hud = InputContext::pass_through("hud")
+            .consume(Key::Tab)
+            .consume(MouseButton::Left);
+        let stack = stack(&[InputContext::modal("game"), hud]);
+        assert!(!sees_key(&stack, "game", Key::Tab));
+        assert!(!stack.sees("game", Some(Binding::Mouse(MouseButton::Left))));
+        assert!(sees_key(&stack, "game", Key::W));
+        assert!(stack.sees("game", Some(Binding::Mouse(MouseButton::Right))));
+        // text and the wheel get through too
+        assert!(stack.sees("game", None));
+    }
+
+    #[test]
+    fn pushed_context_waits_a_frame() {
+        let mut stack = stack(&[InputContext::modal("game")]);
+        stack.push(InputContext::modal("pause"));
+        assert!(!sees_key(&stack, "pause", Key::Escape));
+        assert!(!sees_key(&stack, "game", Key::Escape));
+        stack.begin_frame();
+        assert!(sees_key(&stack, "pause", Key::Escape));
+    }
+
+    #[test]
+    fn popped_context_blocks_until_the_next_frame() {
+        let mut stack = stack(&[InputContext::modal("game"), InputContext::modal("pause")]);
+        assert_eq!(stack.pop().map(|c| c.name), Some("pause".to_string()));
+        assert!(!sees_key(&stack, "game", Key::Enter));
+        stack.begin_frame();
+        assert!(sees_key(&stack, "game", Key::Enter));
+    }
+
+    #[test]
+    fn removed_context_only_blocks_the_ones_under_it() {
+        let mut stack = stack(&[
+            InputContext::modal("world"),
+            InputContext::modal("hud"),
+            InputContext::modal("game"),
+        ]);
+        stack.remove("hud");
+        assert!(!stack.contains("hud"));
+        assert!(sees_key(&stack, "game", Key::Space));
+        assert!(!sees_key(&stack, "world", Key::Space));
+    }
+
+    #[test]
+    fn removing_several_keeps_track_of_their_places() {
+        let mut stack = stack(&[
+            InputContext::modal("a"),
+            InputContext::modal("b"),
+            InputContext::modal("c"),
+            InputContext::pass_through("d"),
+        ]);
+        // c was above b, and still is after a goes and moves both down
+        stack.remove("b");
+        stack.remove("a");
+        assert_eq!(stack.top(), Some("d"));
+        assert!(sees_key(&stack, "c", Key::Space));
+        assert!(sees_key(&stack, "d", Key::Space));
+    }
+}
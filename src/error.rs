@@ -0,0 +1,42 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum WoblError {
+    // reading from or writing to the terminal failed
+    Io(std::io::Error),
+    // stdout is piped or redirected, so there is nothing to draw on, or the backend
+    // needs to ask the terminal something (like inline mode) and it draws to a writer
+    NotATerminal,
+    // device_query couldn't connect to the display server to read the keyboard,
+    // `crossterm_events` reads keys from the terminal instead
+    NoKeyboard,
+    // there is no display to open a window on, a good time to fall back to the terminal
+    NoDisplay(String),
+    // sdl failed at something else (window, renderer, event pump...)
+    Sdl(String),
+    // the font couldn't be loaded or rendered
+    Font(String),
+}
+
+impl fmt::Display for WoblError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WoblError::Io(e) => write!(f, "terminal io failed: {}", e),
+            WoblError::NotATerminal => write!(f, "not drawing to a terminal"),
+            WoblError::NoKeyboard => {
+                write!(f, "couldn't read the keyboard from the display server")
+            }
+            WoblError::NoDisplay(e) => write!(f, "no display available: {}", e),
+            WoblError::Sdl(e) => write!(f, "sdl error: {}", e),
+            WoblError::Font(e) => write!(f, "font error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for WoblError {}
+
+impl From<std::io::Error> for WoblError {
+    fn from(e: std::io::Error) -> Self {
+        WoblError::Io(e)
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{GamepadAxis, GamepadButton, Key, Modifiers, MouseButton};
+
+// one input event, backends report them in the order they happened.
+// positions and sizes are in cells
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    // `key` is the symbol on the key in the current layout, `scancode` is where the key is,
+    // named after the key in that spot on a us qwerty keyboard (the w of wasd is `Key::W`
+    // on azerty too, where it says z). backends that can't tell use the same key for both
+    KeyDown {
+        key: Key,
+        scancode: Key,
+        modifiers: Modifiers,
+    },
+    KeyUp {
+        key: Key,
+        scancode: Key,
+        modifiers: Modifiers,
+    },
+    KeyRepeat {
+        key: Key,
+        scancode: Key,
+        modifiers: Modifiers,
+    },
+    // typed text, see `Wobl::typed_text`
+    Text(String),
+
+    MouseDown {
+        button: MouseButton,
+        x: i32,
+        y: i32,
+    },
+    MouseUp {
+        button: MouseButton,
+        x: i32,
+        y: i32,
+    },
+    MouseMove {
+        x: i32,
+        y: i32,
+    },
+    // positive y scrolls up (away from the user), positive x scrolls right
+    MouseWheel {
+        x: i32,
+        y: i32,
+    },
+
+    GamepadConnected {
+        id: u32,
+        name: String,
+    },
+    GamepadDisconnected {
+        id: u32,
+    },
+    GamepadDown {
+        id: u32,
+        button: GamepadButton,
+    },
+    GamepadUp {
+        id: u32,
+        button: GamepadButton,
+    },
+    GamepadAxis {
+        id: u32,
+        axis: GamepadAxis,
+        value: f32,
+    },
+
+    Resize {
+        width: u32,
+        height: u32,
+    },
+    FocusGained,
+    FocusLost,
+    Quit,
+}
@@ -0,0 +1,266 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// buttons named by position on an xbox style pad
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    A,
+    B,
+    X,
+    Y,
+    Back,
+    Guide,
+    Start,
+    LeftStick,
+    RightStick,
+    LeftShoulder,
+    RightShoulder,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Misc,
+    Paddle1,
+    Paddle2,
+    Paddle3,
+    Paddle4,
+    Touchpad,
+}
+
+// sticks go from -1.0 to 1.0 (y is positive down, like the grid), triggers from 0.0 to 1.0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadStick {
+    Left,
+    Right,
+}
+
+// gamepads are identified by an id that stays the same while they are connected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadEvent {
+    Connected(u32),
+    Disconnected(u32),
+}
+
+#[derive(Debug, Clone, Default)]
+struct Gamepad {
+    name: String,
+    pressed: HashSet<GamepadButton>,
+    just_pressed: HashSet<GamepadButton>,
+    released: HashSet<GamepadButton>,
+    axes: HashMap<GamepadAxis, f32>,
+}
+
+// state of every connected gamepad for one frame
+#[derive(Debug, Clone, Default)]
+pub struct GamepadState {
+    pads: BTreeMap<u32, Gamepad>,
+    events: Vec<GamepadEvent>,
+}
+
+impl GamepadState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // to be called before applying the events of a new frame
+    pub fn begin_frame(&mut self) {
+        self.events.clear();
+        for pad in self.pads.values_mut() {
+            pad.just_pressed.clear();
+            pad.released.clear();
+        }
+    }
+
+    pub fn connect(&mut self, id: u32, name: &str) {
+        let pad = Gamepad {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        if self.pads.insert(id, pad).is_none() {
+            self.events.push(GamepadEvent::Connected(id));
+        }
+    }
+
+    pub fn disconnect(&mut self, id: u32) {
+        if self.pads.remove(&id).is_some() {
+            self.events.push(GamepadEvent::Disconnected(id));
+        }
+    }
+
+    pub fn press(&mut self, id: u32, button: GamepadButton) {
+        if let Some(pad) = self.pads.get_mut(&id)
+            && pad.pressed.insert(button)
+        {
+            pad.just_pressed.insert(button);
+        }
+    }
+
+    pub fn release(&mut self, id: u32, button: GamepadButton) {
+        if let Some(pad) = self.pads.get_mut(&id)
+            && pad.pressed.remove(&button)
+        {
+            pad.released.insert(button);
+        }
+    }
+
+    // lets go of every button and centers every axis, the pads stay connected
+    pub fn release_all(&mut self) {
+        for pad in self.pads.values_mut() {
+            pad.released.extend(pad.pressed.drain());
+            pad.axes.clear();
+        }
+    }
+
+    pub fn set_axis(&mut self, id: u32, axis: GamepadAxis, value: f32) {
+        if let Some(pad) = self.pads.get_mut(&id) {
+            pad.axes.insert(axis, value);
+        }
+    }
+
+    // ids of the connected gamepads
+    pub fn ids(&self) -> Vec<u32> {
+        self.pads.keys().copied().collect()
+    }
+
+    pub fn name(&self, id: u32) -> Option<&str> {
+        self.pads.get(&id).map(|pad| pad.name.as_str())
+    }
+
+    // connects and disconnects since the last frame
+    pub fn events(&self) -> &[GamepadEvent] {
+        &self.events
+    }
+
+    pub fn is_pressed(&self, id: u32, button: GamepadButton) -> bool {
+        self.pads
+            .get(&id)
+            .is_some_and(|pad| pad.pressed.contains(&button))
+    }
+
+    pub fn is_just_pressed(&self, id: u32, button: GamepadButton) -> bool {
+        self.pads
+            .get(&id)
+            .is_some_and(|pad| pad.just_pressed.contains(&button))
+    }
+
+    pub fn is_just_released(&self, id: u32, button: GamepadButton) -> bool {
+        self.pads
+            .get(&id)
+            .is_some_and(|pad| pad.released.contains(&button))
+    }
+
+    // raw axis value, without any dead zone
+    pub fn axis(&self, id: u32, axis: GamepadAxis) -> f32 {
+        self.pads
+            .get(&id)
+            .and_then(|pad| pad.axes.get(&axis).copied())
+            .unwrap_or(0.0)
+    }
+
+    // axis value, 0 inside the dead zone and rescaled so it still reaches 1 outside it
+    pub fn axis_with_dead_zone(&self, id: u32, axis: GamepadAxis, dead_zone: f32) -> f32 {
+        let value = self.axis(id, axis);
+        if value.abs() <= dead_zone {
+            return 0.0;
+        }
+        value.signum() * ((value.abs() - dead_zone) / (1.0 - dead_zone)).min(1.0)
+    }
+
+    // both axes of a stick, with a round dead zone so diagonals aren't cut off
+    pub fn stick(&self, id: u32, stick: GamepadStick, dead_zone: f32) -> (f32, f32) {
+        let (x, y) = match stick {
+            GamepadStick::Left => (
+                self.axis(id, GamepadAxis::LeftX),
+                self.axis(id, GamepadAxis::LeftY),
+            ),
+            GamepadStick::Right => (
+                self.axis(id, GamepadAxis::RightX),
+                self.axis(id, GamepadAxis::RightY),
+            ),
+        };
+        let length = (x * x + y * y).sqrt();
+        if length <= dead_zone {
+            return (0.0, 0.0);
+        }
+        let scaled = ((length - dead_zone) / (1.0 - dead_zone)).min(1.0);
+        (x / length * scaled, y / length * scaled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    fn pad_with(axes: &[(GamepadAxis, f32)]) -> GamepadState {
+        let mut state = GamepadState::new();
+        state.connect(0, "pad");
+        for &(axis, value) in axes {
+            state.set_axis(0, axis, value);
+        }
+        state
+    }
+
+    #[test]
+    fn axis_dead_zone_is_zero_and_rescaled_outside() {
+        let state = pad_with(&[
+            (GamepadAxis::LeftX, 0.1),
+            (GamepadAxis::LeftY, -0.2),
+            (GamepadAxis::RightX, 0.6),
+            (GamepadAxis::RightY, -1.0),
+        ]);
+        assert_eq!(state.axis_with_dead_zone(0, GamepadAxis::LeftX, 0.2), 0.0);
+        assert_eq!(state.axis_with_dead_zone(0, GamepadAxis::LeftY, 0.2), 0.0);
+        assert!(close(
+            state.axis_with_dead_zone(0, GamepadAxis::RightX, 0.2),
+            0.5
+        ));
+        assert!(close(
+            state.axis_with_dead_zone(0, GamepadAxis::RightY, 0.2),
+            -1.0
+        ));
+        // the raw value has no dead zone
+        assert_eq!(state.axis(0, GamepadAxis::LeftX), 0.1);
+    }
+
+    #[test]
+    fn stick_dead_zone_is_round() {
+        // each axis is inside the dead zone, but together they are outside it
+        let state = pad_with(&[(GamepadAxis::LeftX, 0.3), (GamepadAxis::LeftY, 0.3)]);
+        let (x, y) = state.stick(0, GamepadStick::Left, 0.35);
+        assert!(x > 0.0 && close(x, y));
+        assert_eq!(state.axis_with_dead_zone(0, GamepadAxis::LeftX, 0.35), 0.0);
+
+        let state = pad_with(&[(GamepadAxis::RightX, 0.1), (GamepadAxis::RightY, -0.1)]);
+        assert_eq!(state.stick(0, GamepadStick::Right, 0.2), (0.0, 0.0));
+    }
+
+    #[test]
+    fn stick_is_clamped_to_one() {
+        let state = pad_with(&[(GamepadAxis::LeftX, 1.0), (GamepadAxis::LeftY, 1.0)]);
+        let (x, y) = state.stick(0, GamepadStick::Left, 0.2);
+        assert!(close((x * x + y * y).sqrt(), 1.0));
+        assert!(close(x, y));
+    }
+
+    #[test]
+    fn missing_pads_and_released_axes_are_centered() {
+        let mut state = pad_with(&[(GamepadAxis::LeftX, 0.8)]);
+        assert_eq!(state.axis_with_dead_zone(1, GamepadAxis::LeftX, 0.2), 0.0);
+        assert_eq!(state.stick(1, GamepadStick::Left, 0.2), (0.0, 0.0));
+        state.release_all();
+        assert_eq!(state.stick(0, GamepadStick::Left, 0.2), (0.0, 0.0));
+    }
+}
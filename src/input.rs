@@ -0,0 +1,155 @@
+use std::time::Instant;
+
+use crate::{AutoRepeat, Event, GamepadState, KeyState, MouseState};
+
+// everything the engine knows about input for the current frame, built from the events
+#[derive(Debug, Clone, Default)]
+pub struct Input {
+    keys: KeyState,
+    // the same keys by where they are on the keyboard, see `Event::KeyDown`
+    scancodes: KeyState,
+    mouse: MouseState,
+    gamepads: GamepadState,
+    text: String,
+    // the window or terminal is in the background, where keys are meant for something else
+    focus_lost: bool,
+    auto_repeat: AutoRepeat,
+    // uses the repeats the backend reports instead of `auto_repeat`
+    system_key_repeat: bool,
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn begin_frame(&mut self) {
+        self.keys.begin_frame();
+        self.scancodes.begin_frame();
+        self.mouse.begin_frame();
+        self.gamepads.begin_frame();
+        self.text.clear();
+    }
+
+    // while unfocused only focus, resize, quit and gamepads coming and going get through
+    pub fn accepts(&self, event: &Event) -> bool {
+        !self.focus_lost
+            || matches!(
+                event,
+                Event::FocusGained
+                    | Event::FocusLost
+                    | Event::Resize { .. }
+                    | Event::Quit
+                    | Event::GamepadConnected { .. }
+                    | Event::GamepadDisconnected { .. }
+            )
+    }
+
+    // events that aren't `accepts`ed are ignored
+    pub fn apply(&mut self, event: &Event) {
+        if !self.accepts(event) {
+            return;
+        }
+        match event {
+            Event::KeyDown {
+                key,
+                scancode,
+                modifiers,
+            } => {
+                self.keys.press(*key, *modifiers);
+                self.scancodes.press(*scancode, *modifiers);
+            }
+            Event::KeyUp {
+                key,
+                scancode,
+                modifiers,
+            } => {
+                self.keys.set_modifiers(*modifiers);
+                self.keys.release(*key);
+                self.scancodes.set_modifiers(*modifiers);
+                self.scancodes.release(*scancode);
+            }
+            Event::KeyRepeat {
+                key,
+                scancode,
+                modifiers,
+            } => {
+                self.keys.set_modifiers(*modifiers);
+                self.scancodes.set_modifiers(*modifiers);
+                if self.system_key_repeat {
+                    self.keys.repeat(*key);
+                    self.scancodes.repeat(*scancode);
+                }
+            }
+            Event::Text(text) => self.text.push_str(text),
+
+            Event::MouseDown { button, x, y } => {
+                self.mouse.move_to(*x, *y);
+                self.mouse.press(*button);
+            }
+            Event::MouseUp { button, x, y } => {
+                self.mouse.move_to(*x, *y);
+                self.mouse.release(*button);
+            }
+            Event::MouseMove { x, y } => self.mouse.move_to(*x, *y),
+            Event::MouseWheel { x, y } => self.mouse.scroll(*x, *y),
+
+            Event::GamepadConnected { id, name } => self.gamepads.connect(*id, name),
+            Event::GamepadDisconnected { id } => self.gamepads.disconnect(*id),
+            Event::GamepadDown { id, button } => self.gamepads.press(*id, *button),
+            Event::GamepadUp { id, button } => self.gamepads.release(*id, *button),
+            Event::GamepadAxis { id, axis, value } => self.gamepads.set_axis(*id, *axis, *value),
+
+            // whatever is held now is released somewhere else
+            Event::FocusLost => {
+                self.focus_lost = true;
+                self.keys.release_all();
+                self.scancodes.release_all();
+                self.mouse.release_all();
+                self.gamepads.release_all();
+            }
+            Event::FocusGained => self.focus_lost = false,
+            Event::Resize { .. } | Event::Quit => {}
+        }
+    }
+
+    // to be called after applying the events of a frame, with the frame's time
+    pub fn end_frame(&mut self, now: Instant) {
+        if !self.system_key_repeat {
+            self.keys.auto_repeat(now, self.auto_repeat);
+            self.scancodes.auto_repeat(now, self.auto_repeat);
+        }
+    }
+
+    pub fn set_auto_repeat(&mut self, auto_repeat: AutoRepeat) {
+        self.auto_repeat = auto_repeat;
+    }
+
+    pub fn set_system_key_repeat(&mut self, system: bool) {
+        self.system_key_repeat = system;
+    }
+
+    pub fn keys(&self) -> &KeyState {
+        &self.keys
+    }
+
+    pub fn scancodes(&self) -> &KeyState {
+        &self.scancodes
+    }
+
+    pub fn mouse(&self) -> &MouseState {
+        &self.mouse
+    }
+
+    pub fn gamepads(&self) -> &GamepadState {
+        &self.gamepads
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_focused(&self) -> bool {
+        !self.focus_lost
+    }
+}
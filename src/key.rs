@@ -1,3 +1,10 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::{Combo, Modifiers};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Key {
     A,
@@ -51,6 +58,18 @@ pub enum Key {
     F10,
     F11,
     F12,
+    F13,
+    F14,
+    F15,
+    F16,
+    F17,
+    F18,
+    F19,
+    F20,
+    F21,
+    F22,
+    F23,
+    F24,
 
     Escape,
     Enter,
@@ -58,16 +77,60 @@ pub enum Key {
     Backspace,
     Space,
 
+    // Navigation and editing
+    Insert,
+    Delete,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+
+    PrintScreen,
+    ScrollLock,
+    Pause,
+    NumLock,
+    Menu,
+
+    // Modifiers, pressed together with the left or right one
     Shift,
     Ctrl,
     Alt,
+    Super,
     CapsLock,
 
+    LeftShift,
+    RightShift,
+    LeftCtrl,
+    RightCtrl,
+    LeftAlt,
+    RightAlt,
+    LeftSuper,
+    RightSuper,
+
     Up,
     Down,
     Left,
     Right,
 
+    // Numpad
+    Numpad0,
+    Numpad1,
+    Numpad2,
+    Numpad3,
+    Numpad4,
+    Numpad5,
+    Numpad6,
+    Numpad7,
+    Numpad8,
+    Numpad9,
+    NumpadAdd,
+    NumpadSubtract,
+    NumpadMultiply,
+    NumpadDivide,
+    NumpadDecimal,
+    NumpadEnter,
+    NumpadEquals,
+
     Minus,
     Equals,
     LeftBracket,
@@ -84,9 +147,157 @@ pub enum Key {
 }
 
 impl Key {
-    // converts key to string
-    pub fn to_string(&self) -> String {
+    // every key except `Unknown`
+    pub const ALL: [Key; 121] = [
+        Key::A,
+        Key::B,
+        Key::C,
+        Key::D,
+        Key::E,
+        Key::F,
+        Key::G,
+        Key::H,
+        Key::I,
+        Key::J,
+        Key::K,
+        Key::L,
+        Key::M,
+        Key::N,
+        Key::O,
+        Key::P,
+        Key::Q,
+        Key::R,
+        Key::S,
+        Key::T,
+        Key::U,
+        Key::V,
+        Key::W,
+        Key::X,
+        Key::Y,
+        Key::Z,
+        Key::Key0,
+        Key::Key1,
+        Key::Key2,
+        Key::Key3,
+        Key::Key4,
+        Key::Key5,
+        Key::Key6,
+        Key::Key7,
+        Key::Key8,
+        Key::Key9,
+        Key::F1,
+        Key::F2,
+        Key::F3,
+        Key::F4,
+        Key::F5,
+        Key::F6,
+        Key::F7,
+        Key::F8,
+        Key::F9,
+        Key::F10,
+        Key::F11,
+        Key::F12,
+        Key::F13,
+        Key::F14,
+        Key::F15,
+        Key::F16,
+        Key::F17,
+        Key::F18,
+        Key::F19,
+        Key::F20,
+        Key::F21,
+        Key::F22,
+        Key::F23,
+        Key::F24,
+        Key::Escape,
+        Key::Enter,
+        Key::Tab,
+        Key::Backspace,
+        Key::Space,
+        Key::Insert,
+        Key::Delete,
+        Key::Home,
+        Key::End,
+        Key::PageUp,
+        Key::PageDown,
+        Key::PrintScreen,
+        Key::ScrollLock,
+        Key::Pause,
+        Key::NumLock,
+        Key::Menu,
+        Key::Shift,
+        Key::Ctrl,
+        Key::Alt,
+        Key::Super,
+        Key::CapsLock,
+        Key::LeftShift,
+        Key::RightShift,
+        Key::LeftCtrl,
+        Key::RightCtrl,
+        Key::LeftAlt,
+        Key::RightAlt,
+        Key::LeftSuper,
+        Key::RightSuper,
+        Key::Up,
+        Key::Down,
+        Key::Left,
+        Key::Right,
+        Key::Numpad0,
+        Key::Numpad1,
+        Key::Numpad2,
+        Key::Numpad3,
+        Key::Numpad4,
+        Key::Numpad5,
+        Key::Numpad6,
+        Key::Numpad7,
+        Key::Numpad8,
+        Key::Numpad9,
+        Key::NumpadAdd,
+        Key::NumpadSubtract,
+        Key::NumpadMultiply,
+        Key::NumpadDivide,
+        Key::NumpadDecimal,
+        Key::NumpadEnter,
+        Key::NumpadEquals,
+        Key::Minus,
+        Key::Equals,
+        Key::LeftBracket,
+        Key::RightBracket,
+        Key::Backslash,
+        Key::Semicolon,
+        Key::Apostrophe,
+        Key::Grave,
+        Key::Comma,
+        Key::Period,
+        Key::Slash,
+    ];
+
+    // the side independent key for left/right modifiers, `LeftShift` -> `Shift`
+    pub fn generic(&self) -> Option<Key> {
+        match self {
+            Key::LeftShift | Key::RightShift => Some(Key::Shift),
+            Key::LeftCtrl | Key::RightCtrl => Some(Key::Ctrl),
+            Key::LeftAlt | Key::RightAlt => Some(Key::Alt),
+            Key::LeftSuper | Key::RightSuper => Some(Key::Super),
+            _ => None,
+        }
+    }
+
+    // the left and right keys of a generic modifier, `Shift` -> `LeftShift`, `RightShift`
+    pub fn sides(&self) -> Option<(Key, Key)> {
         match self {
+            Key::Shift => Some((Key::LeftShift, Key::RightShift)),
+            Key::Ctrl => Some((Key::LeftCtrl, Key::RightCtrl)),
+            Key::Alt => Some((Key::LeftAlt, Key::RightAlt)),
+            Key::Super => Some((Key::LeftSuper, Key::RightSuper)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
             Key::A => "A",
             Key::B => "B",
             Key::C => "C",
@@ -113,7 +324,6 @@ impl Key {
             Key::X => "X",
             Key::Y => "Y",
             Key::Z => "Z",
-
             Key::Key0 => "0",
             Key::Key1 => "1",
             Key::Key2 => "2",
@@ -124,7 +334,6 @@ impl Key {
             Key::Key7 => "7",
             Key::Key8 => "8",
             Key::Key9 => "9",
-
             Key::F1 => "F1",
             Key::F2 => "F2",
             Key::F3 => "F3",
@@ -137,23 +346,68 @@ impl Key {
             Key::F10 => "F10",
             Key::F11 => "F11",
             Key::F12 => "F12",
-
+            Key::F13 => "F13",
+            Key::F14 => "F14",
+            Key::F15 => "F15",
+            Key::F16 => "F16",
+            Key::F17 => "F17",
+            Key::F18 => "F18",
+            Key::F19 => "F19",
+            Key::F20 => "F20",
+            Key::F21 => "F21",
+            Key::F22 => "F22",
+            Key::F23 => "F23",
+            Key::F24 => "F24",
             Key::Escape => "Escape",
             Key::Enter => "Enter",
             Key::Tab => "Tab",
             Key::Backspace => "Backspace",
             Key::Space => "Space",
-
+            Key::Insert => "Insert",
+            Key::Delete => "Delete",
+            Key::Home => "Home",
+            Key::End => "End",
+            Key::PageUp => "PageUp",
+            Key::PageDown => "PageDown",
+            Key::PrintScreen => "PrintScreen",
+            Key::ScrollLock => "ScrollLock",
+            Key::Pause => "Pause",
+            Key::NumLock => "NumLock",
+            Key::Menu => "Menu",
             Key::Shift => "Shift",
             Key::Ctrl => "Ctrl",
             Key::Alt => "Alt",
+            Key::Super => "Super",
             Key::CapsLock => "CapsLock",
-
+            Key::LeftShift => "LeftShift",
+            Key::RightShift => "RightShift",
+            Key::LeftCtrl => "LeftCtrl",
+            Key::RightCtrl => "RightCtrl",
+            Key::LeftAlt => "LeftAlt",
+            Key::RightAlt => "RightAlt",
+            Key::LeftSuper => "LeftSuper",
+            Key::RightSuper => "RightSuper",
             Key::Up => "Up",
             Key::Down => "Down",
             Key::Left => "Left",
             Key::Right => "Right",
-
+            Key::Numpad0 => "Numpad0",
+            Key::Numpad1 => "Numpad1",
+            Key::Numpad2 => "Numpad2",
+            Key::Numpad3 => "Numpad3",
+            Key::Numpad4 => "Numpad4",
+            Key::Numpad5 => "Numpad5",
+            Key::Numpad6 => "Numpad6",
+            Key::Numpad7 => "Numpad7",
+            Key::Numpad8 => "Numpad8",
+            Key::Numpad9 => "Numpad9",
+            Key::NumpadAdd => "NumpadAdd",
+            Key::NumpadSubtract => "NumpadSubtract",
+            Key::NumpadMultiply => "NumpadMultiply",
+            Key::NumpadDivide => "NumpadDivide",
+            Key::NumpadDecimal => "NumpadDecimal",
+            Key::NumpadEnter => "NumpadEnter",
+            Key::NumpadEquals => "NumpadEquals",
             Key::Minus => "-",
             Key::Equals => "=",
             Key::LeftBracket => "[",
@@ -165,9 +419,197 @@ impl Key {
             Key::Comma => ",",
             Key::Period => ".",
             Key::Slash => "/",
-
             Key::Unknown => "Unknown",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseKeyError(String);
+
+impl ParseKeyError {
+    pub(crate) fn new(s: &str) -> Self {
+        ParseKeyError(s.to_string())
+    }
+}
+
+impl fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown key `{}`", self.0)
+    }
+}
+
+impl std::error::Error for ParseKeyError {}
+
+// parses the names `Display` gives, ignoring case
+impl FromStr for Key {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Key::ALL
+            .iter()
+            .find(|key| key.to_string().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| ParseKeyError::new(s))
+    }
+}
+
+// how held keys repeat, for menus and text fields. the engine does it itself
+// so it is the same on every backend, see `Wobl::set_key_repeat`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoRepeat {
+    // how long a key is held before it starts repeating
+    pub delay: Duration,
+    // time between repeats after that
+    pub interval: Duration,
+}
+
+impl AutoRepeat {
+    pub fn new(delay: Duration, interval: Duration) -> Self {
+        AutoRepeat { delay, interval }
+    }
+}
+
+impl Default for AutoRepeat {
+    fn default() -> Self {
+        AutoRepeat::new(Duration::from_millis(400), Duration::from_millis(50))
+    }
+}
+
+// pressed keys for one frame, built from key events
+#[derive(Debug, Clone, Default)]
+pub struct KeyState {
+    pressed: HashSet<Key>,
+    just_pressed: HashSet<Key>,
+    // `just_pressed` in the order the keys went down
+    just_pressed_order: Vec<Key>,
+    released: HashSet<Key>,
+    repeated: HashSet<Key>,
+    // when each held key repeats next
+    next_repeat: HashMap<Key, Instant>,
+    // modifiers held when each key in `just_pressed` went down
+    pressed_with: HashMap<Key, Modifiers>,
+    modifiers: Modifiers,
+}
+
+impl KeyState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // to be called before applying the events of a new frame
+    pub fn begin_frame(&mut self) {
+        self.just_pressed.clear();
+        self.just_pressed_order.clear();
+        self.released.clear();
+        self.repeated.clear();
+        self.pressed_with.clear();
+    }
+
+    // left/right modifiers also press the generic one. `modifiers` is the state the key went down with
+    pub fn press(&mut self, key: Key, modifiers: Modifiers) {
+        self.modifiers = modifiers;
+        if key == Key::Unknown {
+            return;
+        }
+        if self.pressed.insert(key) {
+            self.just_pressed.insert(key);
+            self.just_pressed_order.push(key);
+            self.pressed_with.insert(key, modifiers);
+        }
+        if let Some(generic) = key.generic() {
+            self.press(generic, modifiers);
+        }
+    }
+
+    // for modifier changes that don't come with a key press
+    pub fn set_modifiers(&mut self, modifiers: Modifiers) {
+        self.modifiers = modifiers;
+    }
+
+    // the generic modifier stays pressed while the other side is still held
+    pub fn release(&mut self, key: Key) {
+        if key == Key::Unknown {
+            return;
+        }
+        // a key up for a key that wasn't down (pressed before the game started, or while
+        // unfocused) isn't a release
+        if !self.pressed.remove(&key) {
+            return;
+        }
+        self.next_repeat.remove(&key);
+        self.released.insert(key);
+        if let Some(generic) = key.generic() {
+            let (left, right) = generic.sides().unwrap();
+            if self.pressed.contains(&generic)
+                && !self.pressed.contains(&left)
+                && !self.pressed.contains(&right)
+            {
+                self.release(generic);
+            }
+        }
+    }
+
+    // lets go of everything, like when the window loses focus and the key ups go elsewhere
+    pub fn release_all(&mut self) {
+        self.released.extend(self.pressed.drain());
+        self.next_repeat.clear();
+        self.modifiers = Modifiers::NONE;
+    }
+
+    // a repeat reported by the backend (the system's key repeat)
+    pub fn repeat(&mut self, key: Key) {
+        if self.pressed.contains(&key) {
+            self.repeated.insert(key);
+        }
+    }
+
+    // repeats held keys on the engine's clock, to be called once per frame after the events.
+    // at most one repeat per key and frame, so a slow frame doesn't cause a burst
+    pub fn auto_repeat(&mut self, now: Instant, settings: AutoRepeat) {
+        for &key in &self.pressed {
+            let next = self.next_repeat.entry(key).or_insert(now + settings.delay);
+            if now >= *next {
+                self.repeated.insert(key);
+                *next = (*next + settings.interval).max(now);
+            }
         }
-        .to_string()
+    }
+
+    pub fn is_pressed(&self, key: Key) -> bool {
+        self.pressed.contains(&key)
+    }
+
+    pub fn is_just_pressed(&self, key: Key) -> bool {
+        self.just_pressed.contains(&key)
+    }
+
+    // every key that went down this frame, in order
+    pub fn just_pressed_keys(&self) -> &[Key] {
+        &self.just_pressed_order
+    }
+
+    pub fn is_just_released(&self, key: Key) -> bool {
+        self.released.contains(&key)
+    }
+
+    // not on the frame the key went down, check `is_just_pressed` too for menus
+    pub fn is_repeated(&self, key: Key) -> bool {
+        self.repeated.contains(&key)
+    }
+
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    // the key is held and the held modifiers are exactly the combo's
+    pub fn is_combo_pressed(&self, combo: Combo) -> bool {
+        self.is_pressed(combo.key) && self.modifiers == combo.modifiers
+    }
+
+    // the key went down this frame while holding exactly the combo's modifiers
+    pub fn is_combo_just_pressed(&self, combo: Combo) -> bool {
+        self.pressed_with.get(&combo.key) == Some(&combo.modifiers)
     }
 }
@@ -1,15 +1,48 @@
+mod action;
+pub mod ansi;
 pub mod backend;
 mod cell;
+mod context;
+mod error;
+mod event;
+mod gamepad;
+mod input;
 mod key;
+mod modifiers;
+mod mouse;
+mod palette;
+mod sequence;
 
+use std::time::Instant;
+
+use context::ContextStack;
+
+pub use action::{Actions, ActionsError, Binding};
+pub use ansi::ColorDepth;
 pub use cell::{Attribute, Cell, Color};
-pub use key::Key;
+pub use context::{ContextInput, ContextMode, InputContext};
+pub use error::WoblError;
+pub use event::Event;
+pub use gamepad::{GamepadAxis, GamepadButton, GamepadEvent, GamepadState, GamepadStick};
+pub use input::Input;
+pub use key::{AutoRepeat, Key, KeyState, ParseKeyError};
+pub use modifiers::{Combo, Modifiers};
+pub use mouse::{MouseButton, MouseState};
+pub use palette::Palette;
+pub use sequence::{KeyChord, KeySequence, Sequences};
 
 pub struct Wobl {
     width: u32,
     height: u32,
     buffer: Vec<Cell>,
     backend: Box<dyn backend::Backend>,
+    input: Input,
+    events: Vec<Event>,
+    actions: Actions,
+    sequences: Sequences,
+    contexts: ContextStack,
+    palette: Palette,
+    gamepad_dead_zone: f32,
 }
 
 impl Wobl {
@@ -20,17 +53,24 @@ impl Wobl {
         width: u32,
         height: u32,
         fps: Option<u32>,
-    ) -> Self {
+    ) -> Result<Self, WoblError> {
         let size = (width * height) as usize;
         let mut wobl = Self {
             width,
             height,
             buffer: vec![Cell::empty(); size],
             backend,
+            input: Input::new(),
+            events: Vec::new(),
+            actions: Actions::new(),
+            sequences: Sequences::new(),
+            contexts: ContextStack::default(),
+            palette: Palette::default(),
+            gamepad_dead_zone: 0.15,
         };
         wobl.backend.set_fps(fps);
-        wobl.backend.init(name, width, height);
-        wobl
+        wobl.backend.init(name, width, height)?;
+        Ok(wobl)
     }
 
     fn index(&self, x: u32, y: u32) -> usize {
@@ -38,9 +78,71 @@ impl Wobl {
     }
 
     // to be used at the start/end of the loop - it renders the frame and waits until the next one.
-    pub fn wait_frame(&mut self) {
-        self.flush();
+    // errors mean the terminal or window went away
+    pub fn wait_frame(&mut self) -> Result<(), WoblError> {
+        self.flush()?;
         self.backend.wait_frame();
+        self.update_input()
+    }
+
+    // events that come in while unfocused (like keys typed in another window with
+    // device_query) are left out of `events` too
+    fn update_input(&mut self) -> Result<(), WoblError> {
+        self.input.begin_frame();
+        self.contexts.begin_frame();
+        let mut polled = Vec::new();
+        self.backend.poll_events(&mut polled)?;
+        self.events.clear();
+        for event in polled {
+            if self.input.accepts(&event) {
+                self.input.apply(&event);
+                self.events.push(event);
+            }
+        }
+        let now = Instant::now();
+        self.input.end_frame(now);
+        self.sequences.update(self.input.keys(), now);
+        Ok(())
+    }
+
+    // true on the frame the player asked to quit: closing the window, ctrl-c, or the
+    // terminal being closed or killed. nothing quits on its own, so save or ask first!
+    pub fn quit_requested(&self) -> bool {
+        self.events.contains(&Event::Quit)
+    }
+
+    // false while the window or terminal is in the background, a good time to pause.
+    // input is ignored then, and everything held is released when focus is lost
+    pub fn is_focused(&self) -> bool {
+        self.input.is_focused()
+    }
+
+    // true when the backend can't see key releases (a terminal without the kitty protocol)
+    // and guesses them from the key repeat. holding several keys at once doesn't work then,
+    // so a game might want to tell the player or switch to tap controls
+    pub fn key_releases_synthesized(&self) -> bool {
+        self.backend.synthesizes_key_releases()
+    }
+
+    // every input event since the last frame, in order
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    // input state of this frame, built from `events`
+    pub fn input(&self) -> &Input {
+        &self.input
+    }
+
+    // the colors named and reset colors are drawn with, xterm's by default.
+    // see `Palette` for presets like `Palette::solarized_dark()`
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.backend.set_palette(&palette);
+        self.palette = palette;
     }
 
     // sets the fps
@@ -63,7 +165,7 @@ impl Wobl {
         text: &str,
         fg: Color,
         bg: Color,
-        atr: &Vec<Attribute>,
+        atr: &[Attribute],
     ) {
         let mut cx = x;
         let mut cy = y;
@@ -73,14 +175,14 @@ impl Wobl {
                 cx = x;
                 continue;
             }
-            self.draw_cell(cx, cy, &Cell::new(ch, fg, bg, atr.clone()));
+            self.draw_cell(cx, cy, &Cell::new(ch, fg, bg, atr.to_vec()));
             cx += 1;
         }
     }
 
     // draws text
     pub fn draw_text(&mut self, x: i32, y: i32, text: &str, fg: Color, bg: Color) {
-        self.draw_text_atr(x, y, text, fg, bg, &Vec::new());
+        self.draw_text_atr(x, y, text, fg, bg, &[]);
     }
 
     // clears the screen
@@ -88,28 +190,251 @@ impl Wobl {
         self.buffer = vec![Cell::empty(); self.buffer.len()];
     }
 
-    fn flush(&mut self) {
+    fn flush(&mut self) -> Result<(), WoblError> {
         for y in 0..self.height {
             for x in 0..self.width {
                 let idx = self.index(x, y);
                 let back = self.buffer[idx].clone();
-                self.backend.draw_cell(x, y, &back);
+                self.backend.draw_cell(x, y, &back)?;
             }
         }
-        self.backend.flush();
+        self.backend.flush()
     }
 
     // checks key is pressed
     pub fn is_key_pressed(&self, key: Key) -> bool {
-        self.backend.is_key_pressed(key)
+        self.input.keys().is_pressed(key)
     }
     // checks if key was just pressed
     pub fn is_key_just_pressed(&self, key: Key) -> bool {
-        self.backend.is_key_just_pressed(key)
+        self.input.keys().is_just_pressed(key)
     }
 
     // checks if key was just released
     pub fn is_key_just_released(&self, key: Key) -> bool {
-        self.backend.is_key_just_released(key)
+        self.input.keys().is_just_released(key)
+    }
+
+    // checks if a held key repeated this frame, like in a text field. for menus use
+    // `is_key_just_pressed(key) || is_key_repeated(key)` so the first press counts too
+    pub fn is_key_repeated(&self, key: Key) -> bool {
+        self.input.keys().is_repeated(key)
+    }
+
+    // the same queries by where the key is instead of what it says, named after a us qwerty
+    // keyboard. `is_scancode_pressed(Key::W)` is the key above s on any layout, good for wasd
+    pub fn is_scancode_pressed(&self, scancode: Key) -> bool {
+        self.input.scancodes().is_pressed(scancode)
+    }
+
+    pub fn is_scancode_just_pressed(&self, scancode: Key) -> bool {
+        self.input.scancodes().is_just_pressed(scancode)
+    }
+
+    pub fn is_scancode_just_released(&self, scancode: Key) -> bool {
+        self.input.scancodes().is_just_released(scancode)
+    }
+
+    pub fn is_scancode_repeated(&self, scancode: Key) -> bool {
+        self.input.scancodes().is_repeated(scancode)
+    }
+
+    // sets how long keys are held before repeating and how fast they repeat then
+    pub fn set_key_repeat(&mut self, auto_repeat: AutoRepeat) {
+        self.input.set_auto_repeat(auto_repeat);
+    }
+
+    // repeats keys like the system does (its delay and rate) instead of with `set_key_repeat`.
+    // this differs between backends and terminals, and some don't repeat at all
+    pub fn set_system_key_repeat(&mut self, system: bool) {
+        self.input.set_system_key_repeat(system);
+    }
+
+    // currently held modifiers, for things like shift-click
+    pub fn modifiers(&self) -> Modifiers {
+        self.input.keys().modifiers()
+    }
+
+    // checks if a combo like `Modifiers::CTRL + Key::S` is held
+    pub fn is_combo_pressed(&self, combo: impl Into<Combo>) -> bool {
+        self.input.keys().is_combo_pressed(combo.into())
+    }
+
+    // checks if a combo like `Modifiers::CTRL + Key::S` was just pressed
+    pub fn is_combo_just_pressed(&self, combo: impl Into<Combo>) -> bool {
+        self.input.keys().is_combo_just_pressed(combo.into())
+    }
+
+    // text typed since the last frame, with shift, layouts and composed characters applied
+    pub fn typed_text(&self) -> &str {
+        self.input.text()
+    }
+
+    // the action bindings, rebindable at any time
+    pub fn actions(&self) -> &Actions {
+        &self.actions
+    }
+
+    pub fn actions_mut(&mut self) -> &mut Actions {
+        &mut self.actions
+    }
+
+    pub fn set_actions(&mut self, actions: Actions) {
+        self.actions = actions;
+    }
+
+    // checks if any input bound to the action is pressed
+    pub fn is_action_pressed(&self, action: &str) -> bool {
+        self.actions
+            .is_pressed(action, self.input.keys(), self.input.mouse())
+    }
+
+    // checks if any input bound to the action was just pressed
+    pub fn is_action_just_pressed(&self, action: &str) -> bool {
+        self.actions
+            .is_just_pressed(action, self.input.keys(), self.input.mouse())
+    }
+
+    // checks if the action was just released and nothing else bound to it is held
+    pub fn is_action_just_released(&self, action: &str) -> bool {
+        self.actions
+            .is_just_released(action, self.input.keys(), self.input.mouse())
+    }
+
+    // puts a context on top of the stack, it sees input from the next frame on
+    pub fn push_context(&mut self, context: InputContext) {
+        self.contexts.push(context);
+    }
+
+    // takes the top context off the stack. its input stays consumed until the next frame
+    pub fn pop_context(&mut self) -> Option<InputContext> {
+        self.contexts.pop()
+    }
+
+    // takes a context off the stack wherever it is
+    pub fn remove_context(&mut self, name: &str) {
+        self.contexts.remove(name);
+    }
+
+    pub fn top_context(&self) -> Option<&str> {
+        self.contexts.top()
+    }
+
+    pub fn has_context(&self, name: &str) -> bool {
+        self.contexts.contains(name)
+    }
+
+    // the input as a context on the stack sees it, so a menu and the game under it don't
+    // both react to the same key: `wobl.context("gameplay").is_key_just_pressed(Key::Enter)`.
+    // the queries on `Wobl` itself ignore contexts
+    pub fn context<'a>(&'a self, name: &'a str) -> ContextInput<'a> {
+        ContextInput {
+            name,
+            stack: &self.contexts,
+            input: &self.input,
+            actions: &self.actions,
+        }
+    }
+
+    // key sequences and chords, like `actions` but matched over several key presses
+    pub fn sequences(&self) -> &Sequences {
+        &self.sequences
+    }
+
+    pub fn sequences_mut(&mut self) -> &mut Sequences {
+        &mut self.sequences
+    }
+
+    // checks if the keys of a sequence like `g g` were just pressed one after the other
+    pub fn is_sequence_just_completed(&self, name: &str) -> bool {
+        self.sequences.is_just_completed(name)
+    }
+
+    // checks if the keys of a chord like A+B just went down together
+    pub fn is_chord_just_pressed(&self, name: &str) -> bool {
+        self.sequences.is_chord_just_pressed(name)
+    }
+
+    // mouse position in cells
+    pub fn mouse_position(&self) -> (i32, i32) {
+        self.input.mouse().position()
+    }
+
+    // how many cells the mouse moved since the last frame
+    pub fn mouse_delta(&self) -> (i32, i32) {
+        self.input.mouse().delta()
+    }
+
+    // checks if the mouse moved since the last frame
+    pub fn mouse_moved(&self) -> bool {
+        self.input.mouse().moved()
+    }
+
+    // wheel movement since the last frame, positive y is up
+    pub fn mouse_wheel(&self) -> (i32, i32) {
+        self.input.mouse().wheel()
+    }
+
+    // checks if mouse button is pressed
+    pub fn is_mouse_pressed(&self, button: MouseButton) -> bool {
+        self.input.mouse().is_pressed(button)
+    }
+
+    // checks if mouse button was just pressed
+    pub fn is_mouse_just_pressed(&self, button: MouseButton) -> bool {
+        self.input.mouse().is_just_pressed(button)
+    }
+
+    // checks if mouse button was just released
+    pub fn is_mouse_just_released(&self, button: MouseButton) -> bool {
+        self.input.mouse().is_just_released(button)
+    }
+
+    // ids of the connected gamepads
+    pub fn gamepads(&self) -> Vec<u32> {
+        self.input.gamepads().ids()
+    }
+
+    pub fn gamepad_name(&self, id: u32) -> Option<&str> {
+        self.input.gamepads().name(id)
+    }
+
+    // gamepads connected and disconnected since the last frame
+    pub fn gamepad_events(&self) -> &[GamepadEvent] {
+        self.input.gamepads().events()
+    }
+
+    // checks if gamepad button is pressed
+    pub fn is_gamepad_pressed(&self, id: u32, button: GamepadButton) -> bool {
+        self.input.gamepads().is_pressed(id, button)
+    }
+
+    // checks if gamepad button was just pressed
+    pub fn is_gamepad_just_pressed(&self, id: u32, button: GamepadButton) -> bool {
+        self.input.gamepads().is_just_pressed(id, button)
+    }
+
+    // checks if gamepad button was just released
+    pub fn is_gamepad_just_released(&self, id: u32, button: GamepadButton) -> bool {
+        self.input.gamepads().is_just_released(id, button)
+    }
+
+    // axis value with the dead zone applied
+    pub fn gamepad_axis(&self, id: u32, axis: GamepadAxis) -> f32 {
+        self.input
+            .gamepads()
+            .axis_with_dead_zone(id, axis, self.gamepad_dead_zone)
+    }
+
+    // stick position with a round dead zone applied
+    pub fn gamepad_stick(&self, id: u32, stick: GamepadStick) -> (f32, f32) {
+        self.input
+            .gamepads()
+            .stick(id, stick, self.gamepad_dead_zone)
+    }
+
+    // sets how far sticks and triggers can move before counting, 0.15 by default
+    pub fn set_gamepad_dead_zone(&mut self, dead_zone: f32) {
+        self.gamepad_dead_zone = dead_zone.clamp(0.0, 0.99);
     }
 }
@@ -0,0 +1,124 @@
+use std::fmt;
+use std::ops::{Add, BitOr};
+use std::str::FromStr;
+
+use crate::{Key, ParseKeyError};
+
+// modifier state of a key event, combine them with `|` or `+`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers(0);
+    pub const SHIFT: Modifiers = Modifiers(1);
+    pub const CTRL: Modifiers = Modifiers(1 << 1);
+    pub const ALT: Modifiers = Modifiers(1 << 2);
+    pub const SUPER: Modifiers = Modifiers(1 << 3);
+
+    const NAMES: [(Modifiers, &'static str); 4] = [
+        (Modifiers::CTRL, "Ctrl"),
+        (Modifiers::ALT, "Alt"),
+        (Modifiers::SHIFT, "Shift"),
+        (Modifiers::SUPER, "Super"),
+    ];
+
+    pub fn contains(&self, other: Modifiers) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn set(&mut self, other: Modifiers, value: bool) {
+        if value {
+            self.0 |= other.0;
+        } else {
+            self.0 &= !other.0;
+        }
+    }
+
+    // the modifier a modifier key sets, `LeftCtrl` -> `CTRL`
+    pub fn from_key(key: Key) -> Option<Modifiers> {
+        match key.generic().unwrap_or(key) {
+            Key::Shift => Some(Modifiers::SHIFT),
+            Key::Ctrl => Some(Modifiers::CTRL),
+            Key::Alt => Some(Modifiers::ALT),
+            Key::Super => Some(Modifiers::SUPER),
+            _ => None,
+        }
+    }
+}
+
+impl BitOr for Modifiers {
+    type Output = Modifiers;
+
+    fn bitor(self, rhs: Modifiers) -> Modifiers {
+        Modifiers(self.0 | rhs.0)
+    }
+}
+
+impl Add for Modifiers {
+    type Output = Modifiers;
+
+    fn add(self, rhs: Modifiers) -> Modifiers {
+        self.bitor(rhs)
+    }
+}
+
+impl Add<Key> for Modifiers {
+    type Output = Combo;
+
+    fn add(self, key: Key) -> Combo {
+        Combo::new(self, key)
+    }
+}
+
+// a key pressed while holding exactly these modifiers, like `Modifiers::CTRL + Key::S`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Combo {
+    pub modifiers: Modifiers,
+    pub key: Key,
+}
+
+impl Combo {
+    pub fn new(modifiers: Modifiers, key: Key) -> Self {
+        Combo { modifiers, key }
+    }
+}
+
+impl From<Key> for Combo {
+    fn from(key: Key) -> Self {
+        Combo::new(Modifiers::NONE, key)
+    }
+}
+
+// written like `Ctrl+Shift+S`
+impl fmt::Display for Combo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in Modifiers::NAMES {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+impl FromStr for Combo {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let key = parts.pop().unwrap_or_default().parse::<Key>()?;
+
+        let mut modifiers = Modifiers::NONE;
+        for part in parts {
+            let modifier = Modifiers::from_key(part.parse::<Key>()?)
+                .ok_or_else(|| ParseKeyError::new(part))?;
+            modifiers = modifiers | modifier;
+        }
+
+        Ok(Combo::new(modifiers, key))
+    }
+}
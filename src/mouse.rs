@@ -0,0 +1,94 @@
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    X1,
+    X2,
+}
+
+// mouse state for one frame, positions are in cells (not pixels!)
+#[derive(Debug, Clone, Default)]
+pub struct MouseState {
+    position: (i32, i32),
+    last_position: (i32, i32),
+    pressed: HashSet<MouseButton>,
+    just_pressed: HashSet<MouseButton>,
+    released: HashSet<MouseButton>,
+    wheel: (i32, i32),
+}
+
+impl MouseState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // to be called before applying the events of a new frame
+    pub fn begin_frame(&mut self) {
+        self.last_position = self.position;
+        self.just_pressed.clear();
+        self.released.clear();
+        self.wheel = (0, 0);
+    }
+
+    pub fn move_to(&mut self, x: i32, y: i32) {
+        self.position = (x, y);
+    }
+
+    pub fn press(&mut self, button: MouseButton) {
+        if !self.pressed.contains(&button) {
+            self.just_pressed.insert(button);
+        }
+        self.pressed.insert(button);
+    }
+
+    pub fn release(&mut self, button: MouseButton) {
+        if self.pressed.remove(&button) {
+            self.released.insert(button);
+        }
+    }
+
+    pub fn release_all(&mut self) {
+        self.released.extend(self.pressed.drain());
+    }
+
+    // positive y scrolls up (away from the user), positive x scrolls right
+    pub fn scroll(&mut self, x: i32, y: i32) {
+        self.wheel.0 += x;
+        self.wheel.1 += y;
+    }
+
+    pub fn position(&self) -> (i32, i32) {
+        self.position
+    }
+
+    // how many cells the mouse moved since the last frame
+    pub fn delta(&self) -> (i32, i32) {
+        (
+            self.position.0 - self.last_position.0,
+            self.position.1 - self.last_position.1,
+        )
+    }
+
+    pub fn moved(&self) -> bool {
+        self.position != self.last_position
+    }
+
+    pub fn wheel(&self) -> (i32, i32) {
+        self.wheel
+    }
+
+    pub fn is_pressed(&self, button: MouseButton) -> bool {
+        self.pressed.contains(&button)
+    }
+
+    pub fn is_just_pressed(&self, button: MouseButton) -> bool {
+        self.just_pressed.contains(&button)
+    }
+
+    pub fn is_just_released(&self, button: MouseButton) -> bool {
+        self.released.contains(&button)
+    }
+}
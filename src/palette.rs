@@ -0,0 +1,120 @@
+use crate::{Color, ansi};
+
+// solarized's terminal colors, the same for the dark and light themes
+const SOLARIZED: [(u8, u8, u8); 16] = [
+    (7, 54, 66),
+    (220, 50, 47),
+    (133, 153, 0),
+    (181, 137, 0),
+    (38, 139, 210),
+    (211, 54, 130),
+    (42, 161, 152),
+    (238, 232, 213),
+    (0, 43, 54),
+    (203, 75, 22),
+    (88, 110, 117),
+    (101, 123, 131),
+    (131, 148, 150),
+    (108, 113, 196),
+    (147, 161, 161),
+    (253, 246, 227),
+];
+
+// the rgb values of the 16 named colors and of `Color::Reset`, which terminals take from
+// their theme. backends that draw pixels use it, so a game looks the same everywhere
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    // in ansi order: black, dark red, dark green, dark yellow, dark blue, dark magenta,
+    // dark cyan, grey, then the bright ones from dark grey to white
+    pub colors: [(u8, u8, u8); 16],
+    // `Color::Reset` as a foreground and as a background
+    pub foreground: (u8, u8, u8),
+    pub background: (u8, u8, u8),
+}
+
+impl Palette {
+    pub fn new(
+        colors: [(u8, u8, u8); 16],
+        foreground: (u8, u8, u8),
+        background: (u8, u8, u8),
+    ) -> Self {
+        Palette {
+            colors,
+            foreground,
+            background,
+        }
+    }
+
+    // xterm's default colors, light grey on black
+    pub fn xterm() -> Self {
+        Palette::new(ansi::NAMED.map(|(_, rgb)| rgb), (229, 229, 229), (0, 0, 0))
+    }
+
+    // the ibm pc text mode colors (cga, ega and vga), with brown for dark yellow
+    pub fn cga() -> Self {
+        Palette::new(
+            [
+                (0, 0, 0),
+                (170, 0, 0),
+                (0, 170, 0),
+                (170, 85, 0),
+                (0, 0, 170),
+                (170, 0, 170),
+                (0, 170, 170),
+                (170, 170, 170),
+                (85, 85, 85),
+                (255, 85, 85),
+                (85, 255, 85),
+                (255, 255, 85),
+                (85, 85, 255),
+                (255, 85, 255),
+                (85, 255, 255),
+                (255, 255, 255),
+            ],
+            (170, 170, 170),
+            (0, 0, 0),
+        )
+    }
+
+    pub fn solarized_dark() -> Self {
+        Palette::new(SOLARIZED, (131, 148, 150), (0, 43, 54))
+    }
+
+    pub fn solarized_light() -> Self {
+        Palette::new(SOLARIZED, (101, 123, 131), (253, 246, 227))
+    }
+
+    // rgb of a color drawn as text, `Color::Reset` is the palette's foreground
+    pub fn foreground_rgb(&self, color: Color) -> (u8, u8, u8) {
+        self.rgb(color, self.foreground)
+    }
+
+    // rgb of a color drawn behind text, `Color::Reset` is the palette's background
+    pub fn background_rgb(&self, color: Color) -> (u8, u8, u8) {
+        self.rgb(color, self.background)
+    }
+
+    // the named color closest to an rgb color with this palette, for art limited to 16 colors
+    pub fn nearest_named(&self, rgb: (u8, u8, u8)) -> Color {
+        (0..16)
+            .min_by_key(|&index| ansi::distance(rgb, self.colors[index]))
+            .map_or(Color::Reset, |index| ansi::NAMED[index].0)
+    }
+
+    // the first 16 ansi values are the named colors, the rest are the same everywhere
+    fn rgb(&self, color: Color, reset: (u8, u8, u8)) -> (u8, u8, u8) {
+        match color {
+            Color::Reset => reset,
+            Color::Rgb { r, g, b } => (r, g, b),
+            Color::AnsiValue(index) if index < 16 => self.colors[index as usize],
+            Color::AnsiValue(index) => ansi::ansi_to_rgb(index),
+            named => ansi::named_index(named).map_or(reset, |index| self.colors[index]),
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::xterm()
+    }
+}
@@ -0,0 +1,345 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::{Key, KeyState, Modifiers};
+
+// keys pressed one after the other, like `g g` in vim, a fighting game motion or a cheat code.
+// each key has to come within `window` of the one before it, with no other key in between.
+// modifier keys don't count, so shift can be held or let go in the middle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySequence {
+    pub keys: Vec<Key>,
+    pub window: Duration,
+}
+
+impl KeySequence {
+    pub fn new(keys: &[Key], window: Duration) -> Self {
+        KeySequence {
+            keys: keys.to_vec(),
+            window,
+        }
+    }
+}
+
+// keys pressed together in any order, like A+B. all of them have to be held at once,
+// after going down within `window` of each other
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyChord {
+    pub keys: Vec<Key>,
+    pub window: Duration,
+}
+
+impl KeyChord {
+    pub fn new(keys: &[Key], window: Duration) -> Self {
+        KeyChord {
+            keys: keys.to_vec(),
+            window,
+        }
+    }
+}
+
+// named sequences and chords, and the presses they are matched against.
+// times come from the frame clock, so every key that went down in a frame counts
+// as pressed at the same moment, whatever the frame rate
+#[derive(Debug, Clone, Default)]
+pub struct Sequences {
+    sequences: BTreeMap<String, KeySequence>,
+    chords: BTreeMap<String, KeyChord>,
+    // recent presses, oldest first. they are numbered so one press can't complete
+    // the same sequence twice (`g g g` is one `g g`, not two)
+    history: VecDeque<(u64, Key, Instant)>,
+    presses: u64,
+    // the last press each sequence used up
+    used: HashMap<String, u64>,
+    // when each held key went down
+    down_at: HashMap<Key, Instant>,
+    completed: HashSet<String>,
+    chorded: HashSet<String>,
+}
+
+impl Sequences {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // adds or replaces a sequence
+    pub fn add_sequence(&mut self, name: &str, sequence: KeySequence) {
+        self.sequences.insert(name.to_string(), sequence);
+        self.used.insert(name.to_string(), self.presses);
+    }
+
+    // adds or replaces a chord
+    pub fn add_chord(&mut self, name: &str, chord: KeyChord) {
+        self.chords.insert(name.to_string(), chord);
+    }
+
+    // removes the sequence or chord with this name
+    pub fn remove(&mut self, name: &str) {
+        self.sequences.remove(name);
+        self.chords.remove(name);
+        self.used.remove(name);
+    }
+
+    pub fn sequence(&self, name: &str) -> Option<&KeySequence> {
+        self.sequences.get(name)
+    }
+
+    pub fn chord(&self, name: &str) -> Option<&KeyChord> {
+        self.chords.get(name)
+    }
+
+    // to be called once per frame after the key events, with the frame's time
+    pub fn update(&mut self, keys: &KeyState, now: Instant) {
+        self.completed.clear();
+        self.chorded.clear();
+        // held keys, plus the ones that go down this frame below
+        self.down_at.retain(|key, _| keys.is_pressed(*key));
+
+        let longest = self.sequences.values().map(|s| s.keys.len()).max();
+        for &key in keys.just_pressed_keys() {
+            self.down_at.insert(key, now);
+            self.match_chords(key);
+
+            if Modifiers::from_key(key).is_some() {
+                continue;
+            }
+            self.presses += 1;
+            self.history.push_back((self.presses, key, now));
+            while self.history.len() > longest.unwrap_or(0) {
+                self.history.pop_front();
+            }
+            self.match_sequences();
+        }
+    }
+
+    // a sequence is done when the latest presses are its keys, none of them used up yet
+    fn match_sequences(&mut self) {
+        for (name, sequence) in &self.sequences {
+            let count = sequence.keys.len();
+            if count == 0 || self.history.len() < count {
+                continue;
+            }
+            let used = self.used.get(name).copied().unwrap_or(0);
+            let mut previous: Option<Instant> = None;
+            let matches = self
+                .history
+                .range(self.history.len() - count..)
+                .zip(&sequence.keys)
+                .all(|(&(press, key, at), expected)| {
+                    let in_time = previous.is_none_or(|p| at - p <= sequence.window);
+                    previous = Some(at);
+                    press > used && key == *expected && in_time
+                });
+            if matches {
+                self.completed.insert(name.clone());
+                self.used.insert(name.clone(), self.presses);
+            }
+        }
+    }
+
+    // a chord is done by the key that completes it, so holding it doesn't repeat it.
+    // keys tapped and let go within one slow frame still count as held together
+    fn match_chords(&mut self, key: Key) {
+        for (name, chord) in &self.chords {
+            if !chord.keys.contains(&key) {
+                continue;
+            }
+            let times: Option<Vec<Instant>> = chord
+                .keys
+                .iter()
+                .map(|k| self.down_at.get(k).copied())
+                .collect();
+            let Some(times) = times else {
+                continue;
+            };
+            let (Some(first), Some(last)) = (times.iter().min(), times.iter().max()) else {
+                continue;
+            };
+            if *last - *first <= chord.window {
+                self.chorded.insert(name.clone());
+            }
+        }
+    }
+
+    // true on the frame the last key of the sequence went down
+    pub fn is_just_completed(&self, name: &str) -> bool {
+        self.completed.contains(name)
+    }
+
+    // true on the frame the last key of the chord went down
+    pub fn is_chord_just_pressed(&self, name: &str) -> bool {
+        self.chorded.contains(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a keyboard driven frame by frame, with times in milliseconds from the start
+    struct Keyboard {
+        keys: KeyState,
+        sequences: Sequences,
+        start: Instant,
+    }
+
+    impl Keyboard {
+        fn new() -> Self {
+            let mut sequences = Sequences::new();
+            sequences.add_sequence(
+                "gg",
+                KeySequence::new(&[Key::G, Key::G], Duration::from_millis(300)),
+            );
+            sequences.add_chord(
+                "ab",
+                KeyChord::new(&[Key::A, Key::B], Duration::from_millis(50)),
+            );
+            Keyboard {
+                keys: KeyState::new(),
+                sequences,
+                start: Instant::now(),
+            }
+        }
+
+        // `true` is a key going down, `false` going up
+        fn frame(&mut self, ms: u64, events: &[(Key, bool)]) {
+            self.keys.begin_frame();
+            for &(key, down) in events {
+                if down {
+                    self.keys.press(key, Modifiers::NONE);
+                } else {
+                    self.keys.release(key);
+                }
+            }
+            let now = self.start + Duration::from_millis(ms);
+            self.sequences.update(&self.keys, now);
+        }
+
+        fn tap(&mut self, ms: u64, key: Key) {
+            self.frame(ms, &[(key, true), (key, false)]);
+        }
+
+        fn completed(&self) -> bool {
+            self.sequences.is_just_completed("gg")
+        }
+
+        fn chorded(&self) -> bool {
+            self.sequences.is_chord_just_pressed("ab")
+        }
+    }
+
+    #[test]
+    fn sequence_completes_within_the_window() {
+        let mut keyboard = Keyboard::new();
+        keyboard.tap(0, Key::G);
+        assert!(!keyboard.completed());
+        keyboard.tap(250, Key::G);
+        assert!(keyboard.completed());
+        keyboard.frame(260, &[]);
+        assert!(!keyboard.completed());
+    }
+
+    #[test]
+    fn sequence_times_out() {
+        let mut keyboard = Keyboard::new();
+        keyboard.tap(0, Key::G);
+        keyboard.tap(400, Key::G);
+        assert!(!keyboard.completed());
+        // the late press can still start a new one
+        keyboard.tap(500, Key::G);
+        assert!(keyboard.completed());
+    }
+
+    #[test]
+    fn presses_are_only_used_once() {
+        let mut keyboard = Keyboard::new();
+        keyboard.tap(0, Key::G);
+        keyboard.tap(100, Key::G);
+        assert!(keyboard.completed());
+        // `g g g` is one `g g`, the fourth g makes the second
+        keyboard.tap(200, Key::G);
+        assert!(!keyboard.completed());
+        keyboard.tap(300, Key::G);
+        assert!(keyboard.completed());
+    }
+
+    #[test]
+    fn other_keys_break_a_sequence_but_modifiers_dont() {
+        let mut keyboard = Keyboard::new();
+        keyboard.tap(0, Key::G);
+        keyboard.tap(50, Key::X);
+        keyboard.tap(100, Key::G);
+        assert!(!keyboard.completed());
+
+        keyboard.frame(150, &[(Key::LeftShift, true)]);
+        keyboard.tap(200, Key::G);
+        assert!(keyboard.completed());
+    }
+
+    #[test]
+    fn presses_in_the_same_frame_count() {
+        let mut keyboard = Keyboard::new();
+        keyboard.frame(0, &[(Key::G, true), (Key::G, false), (Key::G, true)]);
+        assert!(keyboard.completed());
+    }
+
+    #[test]
+    fn new_sequences_ignore_earlier_presses() {
+        let mut keyboard = Keyboard::new();
+        keyboard.tap(0, Key::Z);
+        keyboard.sequences.add_sequence(
+            "zz",
+            KeySequence::new(&[Key::Z, Key::Z], Duration::from_millis(300)),
+        );
+        keyboard.tap(100, Key::Z);
+        assert!(!keyboard.sequences.is_just_completed("zz"));
+        keyboard.tap(200, Key::Z);
+        assert!(keyboard.sequences.is_just_completed("zz"));
+
+        keyboard.sequences.remove("zz");
+        keyboard.tap(300, Key::Z);
+        keyboard.tap(400, Key::Z);
+        assert!(!keyboard.sequences.is_just_completed("zz"));
+    }
+
+    #[test]
+    fn chord_fires_once_when_completed_in_time() {
+        let mut keyboard = Keyboard::new();
+        keyboard.frame(0, &[(Key::A, true)]);
+        assert!(!keyboard.chorded());
+        keyboard.frame(30, &[(Key::B, true)]);
+        assert!(keyboard.chorded());
+        // holding it doesn't repeat it
+        keyboard.frame(60, &[]);
+        assert!(!keyboard.chorded());
+    }
+
+    #[test]
+    fn chord_tapped_in_one_frame_counts() {
+        let mut keyboard = Keyboard::new();
+        keyboard.frame(
+            0,
+            &[
+                (Key::A, true),
+                (Key::B, true),
+                (Key::A, false),
+                (Key::B, false),
+            ],
+        );
+        assert!(keyboard.chorded());
+    }
+
+    #[test]
+    fn slow_chord_is_rejected() {
+        let mut keyboard = Keyboard::new();
+        keyboard.frame(0, &[(Key::A, true)]);
+        keyboard.frame(100, &[(Key::B, true)]);
+        assert!(!keyboard.chorded());
+        // a chord needs every key held
+        keyboard.frame(110, &[(Key::A, false), (Key::B, false)]);
+        keyboard.frame(200, &[(Key::A, true)]);
+        keyboard.frame(210, &[(Key::A, false)]);
+        keyboard.frame(220, &[(Key::B, true)]);
+        assert!(!keyboard.chorded());
+    }
+}